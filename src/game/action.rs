@@ -0,0 +1,115 @@
+/// Una acción del jugador, independiente de la tecla o el dispositivo que la
+/// produce. Cada pantalla interpreta las acciones a su manera: en la selección
+/// de nivel, `MoveForward` y `MoveBackward` mueven la selección.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    /// Avanza en la dirección en la que mira el jugador.
+    MoveForward,
+    /// Retrocede sin girar.
+    MoveBackward,
+    /// Se desplaza hacia la izquierda sin girar.
+    StrafeLeft,
+    /// Se desplaza hacia la derecha sin girar.
+    StrafeRight,
+    /// Gira la cámara hacia la izquierda.
+    TurnLeft,
+    /// Gira la cámara hacia la derecha.
+    TurnRight,
+    /// Cambia entre la vista cenital y la vista en primera persona.
+    ToggleCamera,
+    /// Activa o desactiva el modo de medio bloque.
+    ToggleHalfBlock,
+    /// Activa o desactiva el mapa en Braille.
+    ToggleBraille,
+    /// Guarda una captura de la vista actual.
+    Screenshot,
+    /// Abre la pantalla de configuración de teclas.
+    OpenBindings,
+    /// Muestra la página anterior de una lista.
+    PageUp,
+    /// Muestra la página siguiente de una lista.
+    PageDown,
+    /// Elige la entrada indicada de la página actual de una lista, desde 0.
+    SelectSlot(usize),
+    /// Acepta la opción actual.
+    Confirm,
+    /// Vuelve a la pantalla anterior, o sale del juego si no hay ninguna.
+    Back,
+    /// Sale del juego.
+    Quit,
+}
+
+impl Action {
+    /// Las acciones que se pueden asignar a teclas, en el orden en que aparecen
+    /// en el archivo de teclas y en la pantalla de configuración. `SelectSlot`
+    /// queda fuera: siempre usa las teclas `1` a `9`.
+    pub const BINDABLE: [Action; 16] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::ToggleCamera,
+        Action::ToggleHalfBlock,
+        Action::ToggleBraille,
+        Action::Screenshot,
+        Action::OpenBindings,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Confirm,
+        Action::Back,
+        Action::Quit,
+    ];
+
+    /// Devuelve el nombre de la acción en el archivo de teclas.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBackward => "move_backward",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::ToggleCamera => "toggle_camera",
+            Action::ToggleHalfBlock => "toggle_half_block",
+            Action::ToggleBraille => "toggle_braille",
+            Action::Screenshot => "screenshot",
+            Action::OpenBindings => "open_bindings",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::SelectSlot(_) => "select_slot",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// Devuelve la acción asignable con el nombre indicado.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::BINDABLE.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Devuelve una descripción corta de la acción para mostrarla en pantalla.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Avanzar",
+            Action::MoveBackward => "Retroceder",
+            Action::StrafeLeft => "Paso a la izquierda",
+            Action::StrafeRight => "Paso a la derecha",
+            Action::TurnLeft => "Girar a la izquierda",
+            Action::TurnRight => "Girar a la derecha",
+            Action::ToggleCamera => "Cambiar vista",
+            Action::ToggleHalfBlock => "Medio bloque",
+            Action::ToggleBraille => "Mapa en Braille",
+            Action::Screenshot => "Captura de pantalla",
+            Action::OpenBindings => "Configurar teclas",
+            Action::PageUp => "Página anterior",
+            Action::PageDown => "Página siguiente",
+            Action::SelectSlot(_) => "Elegir entrada",
+            Action::Confirm => "Aceptar",
+            Action::Back => "Volver",
+            Action::Quit => "Salir",
+        }
+    }
+}
@@ -32,3 +32,9 @@ impl Camera {
         };
     }
 }
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
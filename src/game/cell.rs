@@ -0,0 +1,326 @@
+use crossterm::style::Color;
+
+use crate::graphics::Pixel;
+use crate::math::Vec2;
+
+/// El color de una llave y de las puertas que abre.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum KeyColor {
+    Red,
+    Blue,
+    Purple,
+}
+
+impl KeyColor {
+    /// Todos los colores de llave.
+    pub const ALL: [KeyColor; 3] = [KeyColor::Red, KeyColor::Blue, KeyColor::Purple];
+
+    /// Devuelve el nombre del color, concordado con «llave» y «puerta».
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyColor::Red => "roja",
+            KeyColor::Blue => "azul",
+            KeyColor::Purple => "morada",
+        }
+    }
+
+    /// Devuelve el color con el que se dibujan la llave y sus puertas.
+    pub fn color(self) -> Color {
+        match self {
+            KeyColor::Red => Color::DarkRed,
+            KeyColor::Blue => Color::Blue,
+            KeyColor::Purple => Color::Magenta,
+        }
+    }
+}
+
+/// Uno de los cuatro sentidos de la cuadrícula, en el que empuja una cinta.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Heading {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Heading {
+    /// Todos los sentidos.
+    pub const ALL: [Heading; 4] = [Heading::North, Heading::South, Heading::East, Heading::West];
+
+    /// Devuelve el desplazamiento `(dx, dy)` de una celda en este sentido.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Heading::North => (0, -1),
+            Heading::South => (0, 1),
+            Heading::East => (1, 0),
+            Heading::West => (-1, 0),
+        }
+    }
+
+    /// Devuelve el vector unitario de este sentido.
+    pub fn vector(self) -> Vec2 {
+        let (dx, dy) = self.offset();
+        Vec2::new(dx as f32, dy as f32)
+    }
+
+    /// Indica si un desplazamiento `(dx, dy)` va, aunque sea en parte, en contra
+    /// de este sentido.
+    pub fn opposes(self, dx: f32, dy: f32) -> bool {
+        let (hx, hy) = self.offset();
+        dx * (hx as f32) + dy * (hy as f32) < 0.0
+    }
+}
+
+/// Enumera los tipos de celda que pueden aparecer en el mapa de un nivel.
+///
+/// Cada tipo define en un único lugar cómo se escribe en los archivos de nivel,
+/// cómo se dibuja y cómo interactúa con el jugador.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Cell {
+    /// Espacio vacío por el que se puede caminar.
+    #[default]
+    Floor,
+    /// Pared de ladrillo sólida que bloquea el movimiento y los rayos.
+    Wall,
+    /// Pared de piedra; se comporta como `Wall` pero se dibuja distinto.
+    Stone,
+    /// Seto; se comporta como `Wall` pero se dibuja distinto.
+    Hedge,
+    /// Posición inicial del jugador.
+    Start,
+    /// Meta del nivel.
+    Goal,
+    /// Ítem que el jugador debe recoger.
+    Item,
+    /// Puerta que solo se abre si el jugador tiene la llave de su color. `open`
+    /// indica cuánto está abierta, desde 0 (cerrada) hasta `Cell::DOOR_STEPS`
+    /// (abierta del todo); solo deja pasar al jugador abierta del todo.
+    Door { color: KeyColor, open: u8 },
+    /// Llave que el jugador guarda al pasar por encima.
+    Key(KeyColor),
+    /// Teletransportador. Al entrar en él, el jugador aparece en el otro
+    /// extremo de su pareja, declarada en la cabecera del nivel. El número es
+    /// el índice de la pareja y solo sirve para dibujar igual sus dos extremos.
+    Teleporter(u8),
+    /// Cinta transportadora que empuja al jugador en su sentido y no deja
+    /// recorrerla, ni entrar en ella, en el sentido contrario.
+    OneWay(Heading),
+}
+
+impl Cell {
+    /// Todos los tipos de celda, en el orden en que se documentan. Las puertas
+    /// aparecen cerradas.
+    pub const ALL: [Cell; 18] = [
+        Cell::Floor,
+        Cell::Wall,
+        Cell::Stone,
+        Cell::Hedge,
+        Cell::Start,
+        Cell::Goal,
+        Cell::Item,
+        Cell::Door { color: KeyColor::Red, open: 0 },
+        Cell::Door { color: KeyColor::Blue, open: 0 },
+        Cell::Door { color: KeyColor::Purple, open: 0 },
+        Cell::Key(KeyColor::Red),
+        Cell::Key(KeyColor::Blue),
+        Cell::Key(KeyColor::Purple),
+        Cell::Teleporter(0),
+        Cell::OneWay(Heading::North),
+        Cell::OneWay(Heading::South),
+        Cell::OneWay(Heading::East),
+        Cell::OneWay(Heading::West),
+    ];
+
+    /// Colores con los que se distinguen las parejas de teletransportadores.
+    const TELEPORTER_COLORS: [Color; 4] = [Color::Cyan, Color::DarkYellow, Color::Green, Color::DarkMagenta];
+
+    /// Pasos de la animación de una puerta, de cerrada a abierta del todo.
+    pub const DOOR_STEPS: u8 = 8;
+
+    /// Devuelve la celda correspondiente a un glifo del formato de texto de niveles.
+    pub fn from_glyph(glyph: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|cell| cell.glyph() == glyph)
+    }
+
+    /// Devuelve el glifo con el que se escribe la celda en los archivos de nivel.
+    pub fn glyph(self) -> char {
+        match self {
+            Cell::Floor => '.',
+            Cell::Wall => '#',
+            Cell::Stone => '%',
+            Cell::Hedge => '&',
+            Cell::Start => 'S',
+            Cell::Goal => 'G',
+            Cell::Item => '*',
+            // Las puertas en mayúscula y sus llaves en minúscula.
+            Cell::Door { color: KeyColor::Red, .. } => 'R',
+            Cell::Door { color: KeyColor::Blue, .. } => 'B',
+            Cell::Door { color: KeyColor::Purple, .. } => 'P',
+            Cell::Key(KeyColor::Red) => 'r',
+            Cell::Key(KeyColor::Blue) => 'b',
+            Cell::Key(KeyColor::Purple) => 'p',
+            Cell::Teleporter(_) => 'T',
+            Cell::OneWay(Heading::North) => '^',
+            Cell::OneWay(Heading::South) => 'v',
+            Cell::OneWay(Heading::East) => '>',
+            Cell::OneWay(Heading::West) => '<',
+        }
+    }
+
+    /// Indica si la celda bloquea el movimiento del jugador y los rayos. Las
+    /// puertas son sólidas hasta que se abren del todo.
+    pub fn is_solid(self) -> bool {
+        match self {
+            Cell::Door { open, .. } => open < Self::DOOR_STEPS,
+            _ => self.is_wall(),
+        }
+    }
+
+    /// Indica si la celda es una pared, que nunca deja de bloquear el paso.
+    pub fn is_wall(self) -> bool {
+        matches!(self, Cell::Wall | Cell::Stone | Cell::Hedge)
+    }
+
+    /// Devuelve la fracción abierta de una puerta, entre 0 y 1, o `None` si la
+    /// celda no es una puerta.
+    pub fn door_opening(self) -> Option<f32> {
+        match self {
+            Cell::Door { open, .. } => Some(open as f32 / Self::DOOR_STEPS as f32),
+            _ => None,
+        }
+    }
+
+    /// Devuelve la celda con la puerta cerrada, o la misma celda si no es una
+    /// puerta. Sirve para tratar igual una puerta en cualquier punto de su animación.
+    pub fn closed(self) -> Self {
+        match self {
+            Cell::Door { color, .. } => Cell::Door { color, open: 0 },
+            other => other,
+        }
+    }
+
+    /// Indica si la celda contiene algo que el jugador puede recoger.
+    pub fn is_collectible(self) -> bool {
+        matches!(self, Cell::Item)
+    }
+
+    /// Devuelve la celda que queda después de recoger su contenido: un ítem o
+    /// una llave.
+    pub fn collected(self) -> Self {
+        if self.is_collectible() || matches!(self, Cell::Key(_)) {
+            Cell::Floor
+        } else {
+            self
+        }
+    }
+
+    /// Devuelve la altura relativa a una pared con la que la celda se dibuja como
+    /// sprite en la vista en primera persona, o `None` si no se dibuja como sprite.
+    pub fn sprite_scale(self) -> Option<f32> {
+        match self {
+            Cell::Item => Some(0.35),
+            Cell::Key(_) => Some(0.3),
+            Cell::Goal => Some(0.8),
+            _ => None,
+        }
+    }
+
+    /// Devuelve el símbolo con el que se dibuja la celda en los mapas.
+    pub fn symbol(self) -> char {
+        match self {
+            Cell::Floor => ' ',
+            Cell::Wall | Cell::Stone | Cell::Hedge => '█',
+            Cell::Start | Cell::Goal => '▓',
+            Cell::Item => '◆',
+            // Las puertas se ven más transparentes a medida que se abren.
+            Cell::Door { open, .. } => match open {
+                0 => '█',
+                open if open < Self::DOOR_STEPS / 2 => '▓',
+                open if open < Self::DOOR_STEPS => '▒',
+                _ => '░',
+            },
+            Cell::Key(_) => '⊶',
+            // Los dos extremos de una pareja llevan el mismo número.
+            Cell::Teleporter(pair) => char::from_digit(pair as u32 + 1, 36).unwrap_or('T'),
+            Cell::OneWay(Heading::North) => '↑',
+            Cell::OneWay(Heading::South) => '↓',
+            Cell::OneWay(Heading::East) => '→',
+            Cell::OneWay(Heading::West) => '←',
+        }
+    }
+
+    /// Devuelve el color con el que se dibuja la celda.
+    pub fn color(self) -> Color {
+        match self {
+            Cell::Floor => Color::Black,
+            Cell::Wall => Color::White,
+            Cell::Stone => Color::Grey,
+            Cell::Hedge => Color::DarkGreen,
+            Cell::Start => Color::Green,
+            Cell::Goal => Color::Red,
+            Cell::Item => Color::Yellow,
+            Cell::Door { color, .. } | Cell::Key(color) => color.color(),
+            Cell::Teleporter(pair) => Self::TELEPORTER_COLORS[pair as usize % Self::TELEPORTER_COLORS.len()],
+            Cell::OneWay(_) => Color::DarkCyan,
+        }
+    }
+
+    /// Devuelve el color de la baldosa del suelo de la celda en la vista en
+    /// primera persona. El inicio, la meta, los ítems, las llaves, los
+    /// teletransportadores y las cintas se marcan en el suelo.
+    pub fn floor_color(self) -> Color {
+        match self {
+            Cell::Start => Color::Green,
+            Cell::Goal => Color::Red,
+            Cell::Item => Color::Yellow,
+            Cell::Key(_) | Cell::Teleporter(_) | Cell::OneWay(_) => self.color(),
+            _ => Color::Grey,
+        }
+    }
+
+    /// Devuelve el píxel con el que se dibuja la celda en los mapas.
+    pub fn pixel(self) -> Pixel {
+        Pixel::new(self.color(), self.symbol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_cell_round_trips_through_its_glyph() {
+        for cell in Cell::ALL {
+            assert_eq!(Cell::from_glyph(cell.glyph()), Some(cell), "glifo {:?}", cell.glyph());
+        }
+        assert_eq!(Cell::from_glyph('?'), None);
+    }
+
+    #[test]
+    fn solid_and_collectible_cells() {
+        for color in KeyColor::ALL {
+            assert!(Cell::Door { color, open: 0 }.is_solid());
+            assert!(Cell::Door { color, open: Cell::DOOR_STEPS - 1 }.is_solid());
+            assert!(!Cell::Door { color, open: Cell::DOOR_STEPS }.is_solid());
+            assert!(!Cell::Door { color, open: 0 }.is_wall());
+            assert!(!Cell::Door { color, open: 0 }.is_collectible());
+
+            // Las llaves se recogen aparte, con su propio contador.
+            assert!(!Cell::Key(color).is_solid());
+            assert!(!Cell::Key(color).is_collectible());
+            assert_eq!(Cell::Key(color).collected(), Cell::Floor);
+        }
+        for heading in Heading::ALL {
+            assert!(!Cell::OneWay(heading).is_solid());
+            assert!(!Cell::OneWay(heading).is_collectible());
+        }
+        assert!(!Cell::Teleporter(0).is_solid());
+        assert!(!Cell::Teleporter(0).is_collectible());
+
+        assert!(Cell::Item.is_collectible());
+        assert_eq!(Cell::Item.collected(), Cell::Floor);
+        for wall in [Cell::Wall, Cell::Stone, Cell::Hedge] {
+            assert!(wall.is_solid() && wall.is_wall() && !wall.is_collectible());
+        }
+    }
+}
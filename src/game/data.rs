@@ -0,0 +1,39 @@
+//! Búsqueda de los archivos de datos del juego (niveles, texturas y teclas).
+//!
+//! Los datos se buscan primero en el directorio actual y después junto al
+//! ejecutable. En las compilaciones de depuración y en las pruebas también se
+//! busca en el directorio del proyecto, para que `cargo run` funcione desde
+//! cualquier directorio; una compilación de lanzamiento no depende de dónde
+//! estaba el código fuente.
+
+use std::path::{Path, PathBuf};
+
+/// Devuelve las rutas donde se busca el archivo o directorio de datos
+/// `relative`, en orden de preferencia.
+pub fn data_paths(relative: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(relative)];
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        paths.push(exe_dir.join(relative));
+    }
+    #[cfg(any(debug_assertions, test))]
+    paths.push(Path::new(env!("CARGO_MANIFEST_DIR")).join(relative));
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_directory_comes_first() {
+        let paths = data_paths("levels");
+        assert_eq!(paths[0], PathBuf::from("levels"));
+        assert!(paths.iter().skip(1).all(|path| path.ends_with("levels") && path.is_absolute()));
+    }
+
+    #[test]
+    fn project_directory_is_a_fallback_in_tests() {
+        let project = Path::new(env!("CARGO_MANIFEST_DIR")).join("levels");
+        assert_eq!(data_paths("levels").last(), Some(&project));
+    }
+}
@@ -0,0 +1,297 @@
+//! Enemigos: entidades hostiles que patrullan o deambulan por el nivel y
+//! persiguen al jugador cuando lo ven.
+
+use std::collections::VecDeque;
+
+use crossterm::style::Color;
+
+use crate::game::{Cell, Entity, EntityType, Grid, Heuristic, Player};
+use crate::graphics::{cast_ray, Pixel};
+use crate::math::{Rng, Vec2};
+
+/// Velocidad de un enemigo que patrulla o deambula, en celdas por segundo.
+pub const ENEMY_SPEED: f32 = 1.5;
+/// Velocidad de un enemigo mientras persigue al jugador, en celdas por segundo.
+pub const ENEMY_CHASE_SPEED: f32 = 2.2;
+/// Distancia máxima, en celdas, a la que un enemigo ve al jugador.
+pub const ENEMY_SIGHT_RANGE: f32 = 6.0;
+/// Radio del círculo con el que un enemigo toca al jugador, en celdas.
+pub const ENEMY_RADIUS: f32 = 0.3;
+
+/// Lo que hace un enemigo mientras no ve al jugador.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EnemyBehavior {
+    /// Recorre en bucle una ruta de celdas `(x, y)`, yendo de cada una a la
+    /// siguiente por el camino más corto.
+    Patrol(Vec<(usize, usize)>),
+    /// Camina al azar de una celda a otra vecina.
+    Wander,
+}
+
+/// La posición inicial y el comportamiento de un enemigo, tal como se declaran
+/// en el nivel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnemySpawn {
+    /// La celda `(x, y)` en la que aparece.
+    pub position: (usize, usize),
+    /// Lo que hace mientras no ve al jugador.
+    pub behavior: EnemyBehavior,
+}
+
+/// Un enemigo en juego. Se mueve de centro a centro de celda y, mientras ve al
+/// jugador, lo persigue por el camino más corto (A*); cuando lo pierde de
+/// vista, va hasta donde lo vio por última vez y vuelve a su comportamiento.
+pub struct Enemy {
+    /// La entidad con la que se dibuja el enemigo.
+    pub entity: Entity,
+    behavior: EnemyBehavior,
+    /// Índice de la celda de la ruta de patrulla a la que se dirige.
+    waypoint: usize,
+    /// Celdas que le quedan por recorrer hasta su destino actual.
+    path: VecDeque<(usize, usize)>,
+    /// Celda en la que vio al jugador por última vez, mientras lo persigue.
+    chase_target: Option<(usize, usize)>,
+    rng: Rng,
+}
+
+impl Enemy {
+    /// Crea un enemigo en su posición inicial. La semilla decide sus pasos al deambular.
+    pub fn spawn(spawn: &EnemySpawn, seed: u64) -> Self {
+        let (x, y) = spawn.position;
+        Self {
+            entity: Entity::new(x as f32 + 0.5, y as f32 + 0.5, Self::pixel(false), EntityType::Enemy),
+            behavior: spawn.behavior.clone(),
+            waypoint: 0,
+            path: VecDeque::new(),
+            chase_target: None,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Devuelve el píxel con el que se dibuja el enemigo: más vivo mientras persigue.
+    fn pixel(chasing: bool) -> Pixel {
+        Pixel::new(if chasing { Color::Red } else { Color::DarkRed }, '●')
+    }
+
+    /// Devuelve la posición del enemigo en el mapa.
+    pub fn position(&self) -> Vec2 {
+        self.entity.position
+    }
+
+    /// Indica si el enemigo está persiguiendo al jugador.
+    pub fn is_chasing(&self) -> bool {
+        self.chase_target.is_some()
+    }
+
+    /// Indica si el enemigo toca al jugador.
+    pub fn touches(&self, player: &Player) -> bool {
+        let (dx, dy) = (player.position.x - self.position().x, player.position.y - self.position().y);
+        let reach = player.radius + ENEMY_RADIUS;
+        dx * dx + dy * dy < reach * reach
+    }
+
+    /// Indica si el enemigo ve un punto: si está a su alcance y ningún rayo
+    /// lanzado hacia él choca antes con una pared o una puerta cerrada.
+    pub fn sees(&self, target: Vec2, map: &[Vec<Cell>]) -> bool {
+        let offset = Vec2::new(target.x - self.position().x, target.y - self.position().y);
+        let distance = offset.length();
+        if distance > ENEMY_SIGHT_RANGE {
+            return false;
+        }
+        distance == 0.0 || cast_ray(self.position(), offset.normalize(), map).distance > distance
+    }
+
+    /// Avanza `dt` segundos: decide adónde ir según vea o no al jugador y se
+    /// mueve hacia allí.
+    pub fn update(&mut self, dt: f32, player: Vec2, map: &[Vec<Cell>]) {
+        let cell = grid_cell(self.position());
+
+        if self.sees(player, map) {
+            let target = grid_cell(player);
+            if self.chase_target != Some(target) {
+                self.chase_target = Some(target);
+                // Sigue hacia la celda a la que ya iba para no dar marcha atrás a medio paso.
+                let next = self.path.front().copied();
+                let from = next.unwrap_or(cell);
+                self.path = next.into_iter().chain(find_path(map, from, target).unwrap_or_default()).collect();
+            }
+        }
+
+        // Sin camino pendiente: si lo perseguía, ya llegó adonde vio al jugador
+        // y deja de perseguirlo; si no, elige su siguiente destino.
+        if self.path.is_empty() && self.chase_target.take().is_none() {
+            self.plan(cell, map);
+        }
+
+        self.entity.pixel = Self::pixel(self.is_chasing());
+        let speed = if self.is_chasing() { ENEMY_CHASE_SPEED } else { ENEMY_SPEED };
+        self.walk(speed * dt, map);
+    }
+
+    /// Elige el siguiente destino según el comportamiento del enemigo.
+    fn plan(&mut self, cell: (usize, usize), map: &[Vec<Cell>]) {
+        match &self.behavior {
+            EnemyBehavior::Patrol(route) if !route.is_empty() => {
+                if route[self.waypoint] == cell {
+                    self.waypoint = (self.waypoint + 1) % route.len();
+                }
+                match find_path(map, cell, route[self.waypoint]) {
+                    Some(path) => self.path = path,
+                    // Si no hay camino (por ejemplo, tras una puerta cerrada), pasa a la siguiente.
+                    None => self.waypoint = (self.waypoint + 1) % route.len(),
+                }
+            }
+            EnemyBehavior::Patrol(_) => {}
+            EnemyBehavior::Wander => {
+                let neighbors: Vec<_> = Grid::walkable(map).neighbors(cell).map(|(next, _)| next).collect();
+                if !neighbors.is_empty() {
+                    self.path.push_back(*self.rng.choose(&neighbors));
+                }
+            }
+        }
+    }
+
+    /// Recorre hasta `distance` celdas de su camino, de centro a centro de celda.
+    fn walk(&mut self, mut distance: f32, map: &[Vec<Cell>]) {
+        while let Some(&(x, y)) = self.path.front() {
+            // Una puerta que se cerró por el camino obliga a buscar otro.
+            if map[y][x].is_solid() {
+                self.path.clear();
+                return;
+            }
+
+            let target = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let offset = Vec2::new(target.x - self.position().x, target.y - self.position().y);
+            let remaining = offset.length();
+            if remaining > distance {
+                let dir = offset.normalize();
+                self.entity.position = Vec2::new(
+                    self.position().x + dir.x * distance,
+                    self.position().y + dir.y * distance,
+                );
+                return;
+            }
+
+            self.entity.position = target;
+            self.path.pop_front();
+            distance -= remaining;
+        }
+    }
+}
+
+/// Devuelve la celda del mapa que contiene un punto.
+fn grid_cell(position: Vec2) -> (usize, usize) {
+    (position.x as usize, position.y as usize)
+}
+
+/// Busca el camino más corto de `start` a `goal` por las celdas por las que
+/// puede caminar un enemigo. Devuelve las celdas del camino sin incluir
+/// `start`, o `None` si no hay ninguno.
+fn find_path(map: &[Vec<Cell>], start: (usize, usize), goal: (usize, usize)) -> Option<VecDeque<(usize, usize)>> {
+    Grid::walkable(map).find_path(start, goal, Heuristic::Manhattan).map(VecDeque::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{KeyColor, DEFAULT_PLAYER_RADIUS};
+
+    /// Un punto tan lejano que ningún enemigo lo ve.
+    const OUT_OF_SIGHT: Vec2 = Vec2 { x: 100.0, y: 100.0 };
+
+    /// Crea un mapa a partir de sus filas de glifos.
+    fn map(rows: &[&str]) -> Vec<Vec<Cell>> {
+        rows.iter()
+            .map(|row| row.chars().map(|glyph| Cell::from_glyph(glyph).unwrap()).collect())
+            .collect()
+    }
+
+    fn patrol(position: (usize, usize), route: &[(usize, usize)]) -> Enemy {
+        Enemy::spawn(
+            &EnemySpawn {
+                position,
+                behavior: EnemyBehavior::Patrol(route.to_vec()),
+            },
+            0,
+        )
+    }
+
+    /// Avanza al enemigo cuadro a cuadro y devuelve las celdas por las que
+    /// pasa, sin repetir la misma dos veces seguidas.
+    fn visited(enemy: &mut Enemy, frames: usize, player: Vec2, map: &[Vec<Cell>]) -> Vec<(usize, usize)> {
+        let mut cells = vec![grid_cell(enemy.position())];
+        for _ in 0..frames {
+            enemy.update(0.1, player, map);
+            let cell = grid_cell(enemy.position());
+            if cells.last() != Some(&cell) {
+                cells.push(cell);
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn a_patrol_loops_through_its_waypoints() {
+        let map = map(&["#####", "#...#", "#####"]);
+        let mut enemy = patrol((1, 1), &[(1, 1), (3, 1)]);
+        let cells = visited(&mut enemy, 60, OUT_OF_SIGHT, &map);
+        assert_eq!(cells[..7], [(1, 1), (2, 1), (3, 1), (2, 1), (1, 1), (2, 1), (3, 1)]);
+        assert!(!enemy.is_chasing());
+    }
+
+    #[test]
+    fn a_walled_off_waypoint_is_skipped() {
+        let map = map(&["#######", "#...#.#", "#######"]);
+        let mut enemy = patrol((1, 1), &[(1, 1), (5, 1), (3, 1)]);
+        let cells = visited(&mut enemy, 60, OUT_OF_SIGHT, &map);
+        assert_eq!(cells[..5], [(1, 1), (2, 1), (3, 1), (2, 1), (1, 1)]);
+        assert!(!cells.contains(&(5, 1)));
+    }
+
+    #[test]
+    fn chases_the_player_until_the_last_seen_cell() {
+        let map = map(&["########", "#......#", "########"]);
+        let mut enemy = patrol((1, 1), &[(1, 1)]);
+
+        enemy.update(0.1, Vec2::new(5.5, 1.5), &map);
+        assert!(enemy.is_chasing());
+        assert!(enemy.position().x > 1.5);
+
+        // El jugador desaparece de su vista: el enemigo sigue hasta donde lo vio.
+        let mut frames = 0;
+        while enemy.is_chasing() {
+            enemy.update(0.1, OUT_OF_SIGHT, &map);
+            frames += 1;
+            assert!(frames < 100, "el enemigo no deja de perseguir");
+        }
+        assert_eq!(grid_cell(enemy.position()), (5, 1));
+
+        // Después vuelve a su patrulla.
+        let cells = visited(&mut enemy, 40, OUT_OF_SIGHT, &map);
+        assert_eq!(cells.last(), Some(&(1, 1)));
+    }
+
+    #[test]
+    fn a_closed_door_blocks_the_line_of_sight() {
+        let mut map = map(&["#######", "#..R..#", "#######"]);
+        let enemy = patrol((1, 1), &[(1, 1)]);
+        let player = Vec2::new(5.5, 1.5);
+        assert!(!enemy.sees(player, &map));
+
+        map[1][3] = Cell::Door { color: KeyColor::Red, open: Cell::DOOR_STEPS };
+        assert!(enemy.sees(player, &map));
+        assert!(!enemy.sees(Vec2::new(1.5 + ENEMY_SIGHT_RANGE + 0.1, 1.5), &map));
+    }
+
+    #[test]
+    fn touching_uses_both_radii() {
+        let enemy = patrol((1, 1), &[(1, 1)]);
+        let reach = DEFAULT_PLAYER_RADIUS + ENEMY_RADIUS;
+        assert!(enemy.touches(&Player::new(1.5 + reach - 0.01, 1.5)));
+        assert!(!enemy.touches(&Player::new(1.5 + reach + 0.01, 1.5)));
+
+        // Con un jugador más grande, el enemigo lo toca desde más lejos.
+        let player = Player::new(1.5 + reach + 0.05, 1.5).with_radius(DEFAULT_PLAYER_RADIUS + 0.1);
+        assert!(enemy.touches(&player));
+    }
+}
@@ -2,7 +2,7 @@ use crate::graphics::Pixel;
 use crate::math::Vec2;
 
 /// Enumera los diferentes tipos de entidades que pueden existir en el juego.
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum EntityType {
     Player,    // La entidad controlada por el jugador.
     Enemy,     // Una entidad hostil.
@@ -11,6 +11,7 @@ pub enum EntityType {
 }
 
 /// Representa un objeto o personaje en el mundo del juego.
+#[derive(Clone, Debug)]
 pub struct Entity {
     /// La posición de la entidad en el espacio 2D.
     pub position: Vec2,
@@ -0,0 +1,419 @@
+//! Asignación de teclas a acciones.
+//!
+//! Las teclas se definen en un archivo de texto con una línea por acción:
+//!
+//! ```text
+//! move_forward = w up
+//! turn_left = q
+//! quit = x
+//! ```
+//!
+//! Las letras se escriben tal cual y las teclas especiales por su nombre (`up`,
+//! `enter`, `esc`, `space`, `pageup`, `f1`, ...). Las acciones que no aparecen
+//! conservan sus teclas por defecto. Las líneas vacías y las que empiezan con
+//! `#` se ignoran. Una misma tecla puede quedar asignada a varias acciones; en
+//! ese caso gana la primera y `Keymap::conflicts` lo indica.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
+
+use crate::game::{data_paths, Action};
+
+/// Ruta, relativa a los directorios de datos, del archivo con las teclas del
+/// usuario. El juego guarda en él las teclas que se cambian en la pantalla de
+/// configuración.
+pub const DEFAULT_KEYMAP_FILE: &str = "keys.txt";
+
+/// Las teclas por defecto, incluidas en el ejecutable. El archivo solo se lee
+/// al compilar; el juego nunca escribe en él.
+const BUILTIN_KEYMAP: &str = include_str!("../../keys.default.txt");
+
+/// Nombres de las teclas especiales en el archivo de teclas.
+const KEY_NAMES: [(&str, KeyCode); 15] = [
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("space", KeyCode::Char(' ')),
+    ("tab", KeyCode::Tab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+/// Describe un error en una línea del archivo de teclas.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeymapErrorKind {
+    /// La línea no tiene la forma `<acción> = <tecla> ...`.
+    Malformed,
+    /// La acción no existe o no se puede asignar.
+    UnknownAction(String),
+    /// El nombre de la tecla no se reconoce.
+    UnknownKey(String),
+}
+
+/// Error producido al cargar o guardar un archivo de teclas.
+#[derive(Debug)]
+pub enum KeymapError {
+    /// No se pudo leer o escribir el archivo.
+    Io(PathBuf, io::Error),
+    /// El archivo contiene un error en la línea indicada (base 1).
+    Parse { line: usize, kind: KeymapErrorKind },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            KeymapError::Parse { line, kind } => {
+                write!(f, "línea {}: ", line)?;
+                match kind {
+                    KeymapErrorKind::Malformed => write!(f, "se esperaba `<acción> = <tecla> ...`"),
+                    KeymapErrorKind::UnknownAction(name) => write!(f, "acción desconocida `{}`", name),
+                    KeymapErrorKind::UnknownKey(name) => write!(f, "tecla desconocida `{}`", name),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+/// Las teclas asignadas a cada acción.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    /// Las teclas de cada acción de `Action::BINDABLE`, en el mismo orden.
+    bindings: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Keymap {
+    /// Devuelve las teclas por defecto, incluidas en el ejecutable.
+    pub fn builtin() -> Self {
+        let bindings = Action::BINDABLE.iter().map(|&action| (action, Vec::new())).collect();
+        let mut keymap = Self { bindings };
+        keymap
+            .apply(BUILTIN_KEYMAP)
+            .expect("las teclas incluidas deben ser válidas");
+        keymap
+    }
+
+    /// Devuelve la ruta del archivo de teclas por defecto: el primero que exista
+    /// en los directorios de datos o, si no hay ninguno, el del directorio actual.
+    pub fn default_path() -> PathBuf {
+        data_paths(DEFAULT_KEYMAP_FILE)
+            .into_iter()
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_KEYMAP_FILE))
+    }
+
+    /// Carga las teclas de un archivo.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeymapError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|err| KeymapError::Io(path.to_path_buf(), err))?;
+        Self::parse(&source)
+    }
+
+    /// Interpreta un archivo de teclas. Las acciones que no aparecen conservan
+    /// sus teclas por defecto.
+    pub fn parse(source: &str) -> Result<Self, KeymapError> {
+        let mut keymap = Self::builtin();
+        keymap.apply(source)?;
+        Ok(keymap)
+    }
+
+    /// Guarda las teclas en un archivo con el formato que entiende `load`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), KeymapError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string()).map_err(|err| KeymapError::Io(path.to_path_buf(), err))
+    }
+
+    /// Devuelve la acción asignada a una tecla. Las teclas `1` a `9` eligen una
+    /// entrada de la página actual si no están asignadas a otra acción.
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        let key = normalize(key);
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|&(action, _)| action)
+            .or(match key {
+                KeyCode::Char(digit @ '1'..='9') => Some(Action::SelectSlot(digit as usize - '1' as usize)),
+                _ => None,
+            })
+    }
+
+    /// Devuelve las teclas asignadas a una acción.
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+
+    /// Añade una tecla a una acción, si no la tenía ya.
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        let key = normalize(key);
+        if let Some((_, keys)) = self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    /// Quita todas las teclas de una acción.
+    pub fn clear(&mut self, action: Action) {
+        if let Some((_, keys)) = self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+            keys.clear();
+        }
+    }
+
+    /// Devuelve las otras acciones que comparten alguna tecla con `action`.
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        let keys = self.keys(action);
+        self.bindings
+            .iter()
+            .filter(|(other, other_keys)| *other != action && other_keys.iter().any(|key| keys.contains(key)))
+            .map(|&(other, _)| other)
+            .collect()
+    }
+
+    /// Devuelve las teclas de una acción tal como se muestran en pantalla,
+    /// separadas por `/`.
+    pub fn keys_label(&self, action: Action) -> String {
+        self.keys(action).iter().map(|&key| key_label(key)).collect::<Vec<_>>().join(" / ")
+    }
+
+    /// Devuelve las teclas de un grupo de acciones en columnas: la primera tecla
+    /// de cada acción, luego la segunda, etc. Por ejemplo, `WASD / ↑←↓→` para las
+    /// acciones de movimiento. Solo se muestran las columnas completas.
+    pub fn columns_label(&self, actions: &[Action]) -> String {
+        let mut columns = Vec::new();
+        for column in 0.. {
+            let labels: Option<Vec<String>> = actions
+                .iter()
+                .map(|&action| self.keys(action).get(column).map(|&key| key_label(key)))
+                .collect();
+            let Some(labels) = labels else { break };
+            let separator = if labels.iter().all(|label| label.chars().count() == 1) { "" } else { "," };
+            columns.push(labels.join(separator));
+        }
+        columns.join(" / ")
+    }
+
+    /// Aplica las líneas de un archivo de teclas sobre las asignaciones actuales.
+    fn apply(&mut self, source: &str) -> Result<(), KeymapError> {
+        let error = |line: usize, kind: KeymapErrorKind| KeymapError::Parse { line, kind };
+
+        for (i, line) in source.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, keys) = line
+                .split_once('=')
+                .ok_or_else(|| error(line_no, KeymapErrorKind::Malformed))?;
+            let name = name.trim();
+            let action =
+                Action::from_name(name).ok_or_else(|| error(line_no, KeymapErrorKind::UnknownAction(name.to_string())))?;
+            let keys = keys
+                .split_whitespace()
+                .map(|key| parse_key(key).ok_or_else(|| error(line_no, KeymapErrorKind::UnknownKey(key.to_string()))))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            self.clear(action);
+            for key in keys {
+                self.bind(action, key);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Keymap {
+    /// Escribe las teclas con el formato del archivo de teclas.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Teclas del juego: `<acción> = <tecla> ...`.")?;
+        for (action, keys) in &self.bindings {
+            let names: Vec<String> = keys.iter().filter_map(|&key| key_name(key)).collect();
+            writeln!(f, "{} = {}", action.name(), names.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Devuelve la tecla con el nombre indicado: un carácter o el nombre de una
+/// tecla especial.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let lower = name.to_lowercase();
+    if let Some(&(_, key)) = KEY_NAMES.iter().find(|(key_name, _)| *key_name == lower) {
+        return Some(key);
+    }
+    if let Some(number) = lower.strip_prefix('f').and_then(|number| number.parse::<u8>().ok()) {
+        return (1..=12).contains(&number).then_some(KeyCode::F(number));
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(normalize(KeyCode::Char(c))),
+        _ => None,
+    }
+}
+
+/// Devuelve el nombre de una tecla en el archivo de teclas, o `None` si la
+/// tecla no se puede escribir en él (y, por tanto, no se puede asignar).
+/// `parse_key` entiende todos los nombres que devuelve.
+pub fn key_name(key: KeyCode) -> Option<String> {
+    if let Some(&(name, _)) = KEY_NAMES.iter().find(|(_, named)| *named == key) {
+        return Some(name.to_string());
+    }
+    match key {
+        KeyCode::F(number @ 1..=12) => Some(format!("f{}", number)),
+        KeyCode::Char(c) if !c.is_whitespace() && !c.is_control() => Some(c.to_string()),
+        _ => None,
+    }
+}
+
+/// Devuelve una tecla tal como se muestra en pantalla.
+pub fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Char(' ') => "ESPACIO".to_string(),
+        KeyCode::Char(c) => c.to_uppercase().collect(),
+        KeyCode::PageUp => "RePág".to_string(),
+        KeyCode::PageDown => "AvPág".to_string(),
+        KeyCode::Backspace => "RETROCESO".to_string(),
+        KeyCode::Delete => "SUPR".to_string(),
+        other => key_name(other).unwrap_or_else(|| format!("{:?}", other)).to_uppercase(),
+    }
+}
+
+/// Las letras se asignan sin distinguir mayúsculas.
+fn normalize(key: KeyCode) -> KeyCode {
+    match key {
+        KeyCode::Char(c) => KeyCode::Char(c.to_lowercase().next().unwrap_or(c)),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{MediaKeyCode, ModifierKeyCode};
+
+    use super::*;
+
+    /// Teclas con las que comprobar los nombres: las especiales, las de
+    /// función y un buen rango de caracteres.
+    fn sample_keys() -> Vec<KeyCode> {
+        let mut keys: Vec<KeyCode> = KEY_NAMES.iter().map(|&(_, key)| key).collect();
+        keys.extend((0..=30).map(KeyCode::F));
+        keys.extend((0..0x3000).filter_map(char::from_u32).map(KeyCode::Char));
+        keys.extend([
+            KeyCode::BackTab,
+            KeyCode::Null,
+            KeyCode::CapsLock,
+            KeyCode::ScrollLock,
+            KeyCode::NumLock,
+            KeyCode::PrintScreen,
+            KeyCode::Pause,
+            KeyCode::Menu,
+            KeyCode::KeypadBegin,
+            KeyCode::Media(MediaKeyCode::Play),
+            KeyCode::Modifier(ModifierKeyCode::LeftShift),
+        ]);
+        keys
+    }
+
+    #[test]
+    fn every_key_name_parses_back() {
+        for key in sample_keys().into_iter().map(normalize) {
+            if let Some(name) = key_name(key) {
+                assert_eq!(parse_key(&name), Some(key), "la tecla `{}` no se vuelve a leer", name);
+                assert_eq!(key_name(parse_key(&name).unwrap()), Some(name));
+            }
+        }
+    }
+
+    #[test]
+    fn keys_without_a_name_cannot_be_written() {
+        for key in [
+            KeyCode::F(13),
+            KeyCode::BackTab,
+            KeyCode::Media(MediaKeyCode::Play),
+            KeyCode::Char('\u{7}'),
+        ] {
+            assert_eq!(key_name(key), None, "{:?}", key);
+        }
+        assert_eq!(key_name(KeyCode::F(12)), Some("f12".to_string()));
+        assert_eq!(key_name(KeyCode::Char(' ')), Some("space".to_string()));
+    }
+
+    #[test]
+    fn parse_keeps_defaults_for_missing_actions() {
+        let keymap = Keymap::parse("# AZERTY\nmove_forward = Z up\n").unwrap();
+        assert_eq!(keymap.keys(Action::MoveForward), [KeyCode::Char('z'), KeyCode::Up]);
+        assert_eq!(keymap.action(KeyCode::Char('Z')), Some(Action::MoveForward));
+        assert_eq!(keymap.action(KeyCode::Char('w')), None);
+        assert_eq!(keymap.keys(Action::Quit), Keymap::builtin().keys(Action::Quit));
+    }
+
+    #[test]
+    fn parse_reports_the_line_of_each_error() {
+        let cases = [
+            ("quit = x\nmove_forward w", 2, KeymapErrorKind::Malformed),
+            ("\n\nfly = f", 3, KeymapErrorKind::UnknownAction("fly".to_string())),
+            ("quit = x f13", 1, KeymapErrorKind::UnknownKey("f13".to_string())),
+            ("quit = backtab", 1, KeymapErrorKind::UnknownKey("backtab".to_string())),
+        ];
+        for (source, line, kind) in cases {
+            match Keymap::parse(source) {
+                Err(KeymapError::Parse { line: found, kind: found_kind }) => {
+                    assert_eq!((found, found_kind), (line, kind), "{:?}", source);
+                }
+                other => panic!("{:?}: se esperaba un error, se obtuvo {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn saved_keymap_loads_back() {
+        let mut keymap = Keymap::builtin();
+        keymap.clear(Action::Quit);
+        keymap.bind(Action::Quit, KeyCode::F(5));
+        keymap.bind(Action::Quit, KeyCode::Char('#'));
+        keymap.bind(Action::TurnLeft, KeyCode::PageDown);
+
+        let path = std::env::temp_dir().join(format!("raytracer-maze-keys-{}.txt", std::process::id()));
+        keymap.save(&path).unwrap();
+        let loaded = Keymap::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), keymap);
+    }
+
+    #[test]
+    fn builtin_keymap_comes_from_the_default_file() {
+        let builtin = Keymap::builtin();
+        assert_eq!(Keymap::parse(BUILTIN_KEYMAP).unwrap(), builtin);
+        assert_eq!(builtin.action(KeyCode::Char('w')), Some(Action::MoveForward));
+        assert!(!Keymap::default_path().ends_with("keys.default.txt"));
+    }
+}
@@ -0,0 +1,519 @@
+//! Carga de niveles desde archivos de texto plano.
+//!
+//! Un archivo de nivel tiene una cabecera con pares `clave: valor`, una línea en
+//! blanco y después el mapa dibujado con glifos ASCII:
+//!
+//! ```text
+//! name: Nivel 1: El Comienzo
+//! required_items: 3
+//!
+//! ##########
+//! #S..*#...#
+//! #.#....#.#
+//! #..*.#...#
+//! #.#...*..#
+//! #...#..#G#
+//! ##########
+//! ```
+//!
+//! Los glifos de cada tipo de celda se definen en [`Cell::glyph`].
+//!
+//! Cada pareja de teletransportadores `T` se declara en la cabecera con un campo
+//! `teleport` que da las posiciones `x,y` (desde 0) de sus dos extremos. El
+//! campo puede repetirse, una vez por pareja:
+//!
+//! ```text
+//! teleport: 3,1 10,7
+//! ```
+//!
+//! Cada enemigo se declara con un campo `enemy`, también repetible, que da su
+//! comportamiento y su posición inicial. Los que patrullan recorren en bucle
+//! las posiciones indicadas, empezando por la primera:
+//!
+//! ```text
+//! enemy: wander 5,3
+//! enemy: patrol 1,7 8,7 8,9
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{Level, TeleportPair, ValidationReport};
+use crate::game::{Cell, EnemyBehavior, EnemySpawn};
+
+/// Describe el motivo por el que un nivel no pudo interpretarse.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelParseErrorKind {
+    /// Una línea de la cabecera no tiene la forma `clave: valor`.
+    MalformedHeader,
+    /// La cabecera contiene una clave que el formato no reconoce.
+    UnknownField(String),
+    /// Falta un campo obligatorio de la cabecera.
+    MissingField(&'static str),
+    /// El valor de un campo de la cabecera no es válido.
+    InvalidField { field: &'static str, value: String },
+    /// El archivo no contiene ninguna fila de mapa.
+    EmptyMap,
+    /// El mapa contiene un carácter que no corresponde a ninguna celda.
+    UnknownGlyph(char),
+    /// Una fila del mapa no tiene la misma longitud que la primera.
+    RaggedRow { expected: usize, found: usize },
+    /// El mapa no tiene ninguna casilla de inicio `S`.
+    MissingStart,
+    /// El mapa no tiene ninguna casilla de meta `G`.
+    MissingGoal,
+    /// Un campo `teleport` nombra una posición donde no hay un teletransportador.
+    NotATeleporter { x: usize, y: usize },
+    /// Un teletransportador aparece en más de una pareja.
+    DuplicateTeleporter { x: usize, y: usize },
+    /// Un teletransportador del mapa no aparece en ningún campo `teleport`.
+    UnpairedTeleporter,
+    /// Un campo `enemy` nombra una posición fuera del mapa o dentro de una celda sólida.
+    BlockedEnemyPosition { x: usize, y: usize },
+}
+
+/// Error producido al interpretar un nivel, con la posición (base 1) donde ocurrió.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelParseError {
+    /// Línea del archivo donde se detectó el error.
+    pub line: usize,
+    /// Columna de la línea donde se detectó el error.
+    pub column: usize,
+    /// El tipo de error.
+    pub kind: LevelParseErrorKind,
+}
+
+impl LevelParseError {
+    fn new(line: usize, column: usize, kind: LevelParseErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl fmt::Display for LevelParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "línea {}, columna {}: ", self.line, self.column)?;
+        match &self.kind {
+            LevelParseErrorKind::MalformedHeader => {
+                write!(f, "se esperaba una cabecera de la forma `clave: valor`")
+            }
+            LevelParseErrorKind::UnknownField(field) => {
+                write!(f, "campo de cabecera desconocido `{}`", field)
+            }
+            LevelParseErrorKind::MissingField(field) => {
+                write!(f, "falta el campo obligatorio `{}`", field)
+            }
+            LevelParseErrorKind::InvalidField { field, value } => {
+                write!(f, "valor inválido `{}` para el campo `{}`", value, field)
+            }
+            LevelParseErrorKind::EmptyMap => write!(f, "el nivel no contiene ningún mapa"),
+            LevelParseErrorKind::UnknownGlyph(glyph) => {
+                write!(f, "glifo desconocido {:?}", glyph)
+            }
+            LevelParseErrorKind::RaggedRow { expected, found } => write!(
+                f,
+                "la fila tiene {} columnas, pero se esperaban {}",
+                found, expected
+            ),
+            LevelParseErrorKind::MissingStart => {
+                write!(f, "el mapa no tiene casilla de inicio `S`")
+            }
+            LevelParseErrorKind::MissingGoal => write!(f, "el mapa no tiene casilla de meta `G`"),
+            LevelParseErrorKind::NotATeleporter { x, y } => {
+                write!(f, "no hay ningún teletransportador `T` en ({}, {})", x, y)
+            }
+            LevelParseErrorKind::DuplicateTeleporter { x, y } => {
+                write!(f, "el teletransportador en ({}, {}) ya pertenece a otra pareja", x, y)
+            }
+            LevelParseErrorKind::UnpairedTeleporter => {
+                write!(f, "el teletransportador no tiene pareja en ningún campo `teleport`")
+            }
+            LevelParseErrorKind::BlockedEnemyPosition { x, y } => {
+                write!(f, "el enemigo no puede estar en ({}, {}): no es una casilla libre del mapa", x, y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelParseError {}
+
+/// Error producido al cargar un nivel desde un archivo.
+#[derive(Debug)]
+pub enum LevelLoadError {
+    /// No se pudo leer el archivo.
+    Io(PathBuf, io::Error),
+    /// El contenido del archivo no es un nivel válido.
+    Parse(PathBuf, LevelParseError),
+    /// El nivel se interpretó correctamente, pero no se puede jugar o ganar.
+    Invalid(PathBuf, ValidationReport),
+}
+
+impl fmt::Display for LevelLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelLoadError::Io(path, err) => {
+                write!(f, "{}: no se pudo leer el archivo: {}", path.display(), err)
+            }
+            LevelLoadError::Parse(path, err) => write!(f, "{}: {}", path.display(), err),
+            LevelLoadError::Invalid(path, report) => {
+                write!(f, "{}: el nivel no es válido:", path.display())?;
+                for issue in report.errors() {
+                    write!(f, "\n  {}", issue)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LevelLoadError::Io(_, err) => Some(err),
+            LevelLoadError::Parse(_, err) => Some(err),
+            LevelLoadError::Invalid(..) => None,
+        }
+    }
+}
+
+impl Level {
+    /// Interpreta un nivel escrito en el formato de texto plano.
+    pub fn parse(source: &str) -> Result<Self, LevelParseError> {
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        // Cabecera: pares `clave: valor` hasta la primera línea en blanco.
+        let mut name: Option<String> = None;
+        let mut required_items: Option<usize> = None;
+        // Parejas de teletransportadores con la línea y la columna de su valor.
+        let mut teleports: Vec<(usize, usize, TeleportPair)> = Vec::new();
+        // Enemigos con la línea y la columna de su valor.
+        let mut enemies: Vec<(usize, usize, EnemySpawn)> = Vec::new();
+        let mut header_end = 0;
+
+        for (line_no, line) in lines.by_ref() {
+            header_end = line_no;
+            if line.trim().is_empty() {
+                break;
+            }
+
+            let (key, raw_value) = line
+                .split_once(':')
+                .ok_or_else(|| LevelParseError::new(line_no, 1, LevelParseErrorKind::MalformedHeader))?;
+            let value = raw_value.trim();
+            let value_offset = key.len() + 1 + (raw_value.len() - raw_value.trim_start().len());
+            let value_column = line[..value_offset].chars().count() + 1;
+
+            match key.trim() {
+                "name" => name = Some(value.to_string()),
+                "required_items" => {
+                    let count = value.parse().map_err(|_| {
+                        LevelParseError::new(
+                            line_no,
+                            value_column,
+                            LevelParseErrorKind::InvalidField {
+                                field: "required_items",
+                                value: value.to_string(),
+                            },
+                        )
+                    })?;
+                    required_items = Some(count);
+                }
+                "teleport" => {
+                    let pair = parse_teleport(value).ok_or_else(|| {
+                        LevelParseError::new(
+                            line_no,
+                            value_column,
+                            LevelParseErrorKind::InvalidField {
+                                field: "teleport",
+                                value: value.to_string(),
+                            },
+                        )
+                    })?;
+                    teleports.push((line_no, value_column, pair));
+                }
+                "enemy" => {
+                    let spawn = parse_enemy(value).ok_or_else(|| {
+                        LevelParseError::new(
+                            line_no,
+                            value_column,
+                            LevelParseErrorKind::InvalidField {
+                                field: "enemy",
+                                value: value.to_string(),
+                            },
+                        )
+                    })?;
+                    enemies.push((line_no, value_column, spawn));
+                }
+                other => {
+                    return Err(LevelParseError::new(
+                        line_no,
+                        1,
+                        LevelParseErrorKind::UnknownField(other.to_string()),
+                    ));
+                }
+            }
+        }
+
+        let name = name.ok_or_else(|| {
+            LevelParseError::new(header_end, 1, LevelParseErrorKind::MissingField("name"))
+        })?;
+        let required_items = required_items.ok_or_else(|| {
+            LevelParseError::new(header_end, 1, LevelParseErrorKind::MissingField("required_items"))
+        })?;
+
+        // Mapa: el resto de líneas, ignorando las líneas en blanco finales.
+        let mut rows: Vec<(usize, &str)> = lines.collect();
+        while rows.last().is_some_and(|(_, line)| line.trim().is_empty()) {
+            rows.pop();
+        }
+        let first_map_line = rows.first().map(|(line_no, _)| *line_no).ok_or_else(|| {
+            LevelParseError::new(header_end + 1, 1, LevelParseErrorKind::EmptyMap)
+        })?;
+
+        let mut map: Vec<Vec<Cell>> = Vec::with_capacity(rows.len());
+        let mut has_start = false;
+        let mut has_goal = false;
+
+        for (line_no, line) in rows {
+            let mut row = Vec::with_capacity(line.len());
+            for (col, glyph) in line.chars().enumerate() {
+                let cell = Cell::from_glyph(glyph).ok_or_else(|| {
+                    LevelParseError::new(line_no, col + 1, LevelParseErrorKind::UnknownGlyph(glyph))
+                })?;
+                has_start |= cell == Cell::Start;
+                has_goal |= cell == Cell::Goal;
+                row.push(cell);
+            }
+
+            if let Some(first) = map.first() {
+                if row.len() != first.len() {
+                    let column = row.len().min(first.len()) + 1;
+                    return Err(LevelParseError::new(
+                        line_no,
+                        column,
+                        LevelParseErrorKind::RaggedRow {
+                            expected: first.len(),
+                            found: row.len(),
+                        },
+                    ));
+                }
+            } else if row.is_empty() {
+                return Err(LevelParseError::new(line_no, 1, LevelParseErrorKind::EmptyMap));
+            }
+
+            map.push(row);
+        }
+
+        if !has_start {
+            return Err(LevelParseError::new(first_map_line, 1, LevelParseErrorKind::MissingStart));
+        }
+        if !has_goal {
+            return Err(LevelParseError::new(first_map_line, 1, LevelParseErrorKind::MissingGoal));
+        }
+
+        // Cada extremo de una pareja debe ser un teletransportador sin pareja
+        // todavía; la celda guarda el índice de la suya.
+        let mut paired = HashSet::new();
+        let mut teleporters = Vec::with_capacity(teleports.len());
+        for (index, (line_no, column, pair)) in teleports.into_iter().enumerate() {
+            let error = |kind| LevelParseError::new(line_no, column, kind);
+            let index = u8::try_from(index).map_err(|_| {
+                error(LevelParseErrorKind::InvalidField {
+                    field: "teleport",
+                    value: "demasiadas parejas".to_string(),
+                })
+            })?;
+            for (x, y) in pair {
+                if !matches!(map.get(y).and_then(|row| row.get(x)), Some(Cell::Teleporter(_))) {
+                    return Err(error(LevelParseErrorKind::NotATeleporter { x, y }));
+                }
+                if !paired.insert((x, y)) {
+                    return Err(error(LevelParseErrorKind::DuplicateTeleporter { x, y }));
+                }
+                map[y][x] = Cell::Teleporter(index);
+            }
+            teleporters.push(pair);
+        }
+        for (y, row) in map.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if matches!(cell, Cell::Teleporter(_)) && !paired.contains(&(x, y)) {
+                    return Err(LevelParseError::new(
+                        first_map_line + y,
+                        x + 1,
+                        LevelParseErrorKind::UnpairedTeleporter,
+                    ));
+                }
+            }
+        }
+
+        // Los enemigos, y las rutas por las que patrullan, deben estar en celdas libres.
+        for (line_no, column, spawn) in &enemies {
+            let route = match &spawn.behavior {
+                EnemyBehavior::Patrol(route) => route.as_slice(),
+                EnemyBehavior::Wander => &[],
+            };
+            for &(x, y) in std::iter::once(&spawn.position).chain(route) {
+                if map.get(y).and_then(|row| row.get(x)).is_none_or(|cell| cell.is_solid()) {
+                    return Err(LevelParseError::new(
+                        *line_no,
+                        *column,
+                        LevelParseErrorKind::BlockedEnemyPosition { x, y },
+                    ));
+                }
+            }
+        }
+
+        let mut level = Level::new(map, required_items, &name);
+        level.teleporters = teleporters;
+        level.enemies = enemies.into_iter().map(|(_, _, spawn)| spawn).collect();
+        Ok(level)
+    }
+
+    /// Carga un nivel desde un archivo de texto plano y comprueba que se pueda
+    /// ganar. Las advertencias de la validación no impiden cargarlo.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LevelLoadError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|err| LevelLoadError::Io(path.to_path_buf(), err))?;
+        let level = Self::parse(&source)
+            .map_err(|err| LevelLoadError::Parse(path.to_path_buf(), err))?;
+
+        let report = level.validate();
+        if !report.is_valid() {
+            return Err(LevelLoadError::Invalid(path.to_path_buf(), report));
+        }
+        Ok(level)
+    }
+}
+
+/// Interpreta una posición `x,y`.
+fn parse_position(text: &str) -> Option<(usize, usize)> {
+    let (x, y) = text.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Interpreta el valor de un campo `teleport`: dos posiciones `x,y` separadas
+/// por espacios.
+fn parse_teleport(value: &str) -> Option<TeleportPair> {
+    let mut parts = value.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(a), Some(b), None) => Some([parse_position(a)?, parse_position(b)?]),
+        _ => None,
+    }
+}
+
+/// Interpreta el valor de un campo `enemy`: `wander x,y` o `patrol` seguido de
+/// las posiciones de su ruta, la primera de las cuales es donde aparece.
+fn parse_enemy(value: &str) -> Option<EnemySpawn> {
+    let mut parts = value.split_whitespace();
+    let behavior = parts.next()?;
+    let positions: Vec<_> = parts.map(parse_position).collect::<Option<_>>()?;
+    match (behavior, positions.as_slice()) {
+        ("wander", &[position]) => Some(EnemySpawn {
+            position,
+            behavior: EnemyBehavior::Wander,
+        }),
+        ("patrol", &[position, ..]) => Some(EnemySpawn {
+            position,
+            behavior: EnemyBehavior::Patrol(positions),
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Devuelve la línea, la columna y el tipo del error al interpretar `source`.
+    fn error(source: &str) -> (usize, usize, LevelParseErrorKind) {
+        let err = Level::parse(source).err().expect("el nivel no debería interpretarse");
+        (err.line, err.column, err.kind)
+    }
+
+    #[test]
+    fn parses_header_map_teleporters_and_enemies() {
+        let level = Level::parse(
+            "name: Prueba\nrequired_items: 1\nteleport: 1,1 5,1\nenemy: patrol 2,2 4,2\n\n\
+             #######\n#T*.STG\n#.....#\n#######\n\n",
+        )
+        .unwrap();
+        assert_eq!(level.name, "Prueba");
+        assert_eq!(level.required_items, 1);
+        assert_eq!((level.get_width(), level.get_height()), (7, 4));
+        assert_eq!(level.teleport_destination(1, 1), Some((5, 1)));
+        assert_eq!(level.enemies.len(), 1);
+        assert_eq!(level.enemies[0].position, (2, 2));
+    }
+
+    #[test]
+    fn header_errors_point_at_their_line_and_value() {
+        assert_eq!(error("name Prueba\n"), (1, 1, LevelParseErrorKind::MalformedHeader));
+        assert_eq!(
+            error("name: Prueba\ncolor: rojo\n"),
+            (2, 1, LevelParseErrorKind::UnknownField("color".to_string()))
+        );
+        assert_eq!(
+            error("name: Prueba\nrequired_items:  tres\n"),
+            (
+                2,
+                18,
+                LevelParseErrorKind::InvalidField { field: "required_items", value: "tres".to_string() }
+            )
+        );
+        assert_eq!(
+            error("name: Prueba\n\n#SG#\n"),
+            (2, 1, LevelParseErrorKind::MissingField("required_items"))
+        );
+    }
+
+    #[test]
+    fn map_errors_point_at_their_cell() {
+        let header = "name: Prueba\nrequired_items: 0\n\n";
+        assert_eq!(error(header), (4, 1, LevelParseErrorKind::EmptyMap));
+        assert_eq!(
+            error(&format!("{}####\n#S?G\n####\n", header)),
+            (5, 3, LevelParseErrorKind::UnknownGlyph('?'))
+        );
+        assert_eq!(
+            error(&format!("{}####\n#SG\n####\n", header)),
+            (5, 4, LevelParseErrorKind::RaggedRow { expected: 4, found: 3 })
+        );
+        assert_eq!(error(&format!("{}####\n#.G#\n####\n", header)), (4, 1, LevelParseErrorKind::MissingStart));
+        assert_eq!(error(&format!("{}####\n#S.#\n####\n", header)), (4, 1, LevelParseErrorKind::MissingGoal));
+    }
+
+    #[test]
+    fn teleporter_and_enemy_errors_point_at_their_field() {
+        let map = "\n\n######\n#STTG#\n#T...#\n######\n";
+        assert_eq!(
+            error(&format!("name: P\nrequired_items: 0\nteleport: 2,1 4,1{}", map)),
+            (3, 11, LevelParseErrorKind::NotATeleporter { x: 4, y: 1 })
+        );
+        assert_eq!(
+            error(&format!("name: P\nrequired_items: 0\nteleport: 2,1 3,1\nteleport: 3,1 1,2{}", map)),
+            (4, 11, LevelParseErrorKind::DuplicateTeleporter { x: 3, y: 1 })
+        );
+        assert_eq!(
+            error(&format!("name: P\nrequired_items: 0\nteleport: 2,1 3,1{}", map)),
+            (7, 2, LevelParseErrorKind::UnpairedTeleporter)
+        );
+        assert_eq!(
+            error("name: P\nrequired_items: 0\nenemy: wander 0,0\n\n####\n#SG#\n####\n"),
+            (3, 8, LevelParseErrorKind::BlockedEnemyPosition { x: 0, y: 0 })
+        );
+    }
+
+    #[test]
+    fn load_errors_name_the_file() {
+        let err = Level::load("no-existe.txt").err().unwrap();
+        assert!(matches!(err, LevelLoadError::Io(..)));
+        assert!(err.to_string().starts_with("no-existe.txt: "));
+
+        let parse = LevelParseError::new(5, 3, LevelParseErrorKind::UnknownGlyph('?'));
+        let err = LevelLoadError::Parse(PathBuf::from("nivel.txt"), parse);
+        assert_eq!(err.to_string(), "nivel.txt: línea 5, columna 3: glifo desconocido '?'");
+    }
+}
@@ -0,0 +1,93 @@
+//! Definición de los niveles del juego, su carga desde archivos de texto y su validación.
+
+mod loader;
+mod validate;
+
+pub use loader::*;
+pub use validate::*;
+
+use crate::game::{Cell, EnemySpawn, KeyColor};
+
+/// Las posiciones `(x, y)` de los dos extremos de una pareja de teletransportadores.
+pub type TeleportPair = [(usize, usize); 2];
+
+/// Representa un nivel del juego, incluyendo su diseño y objetivos.
+#[derive(Clone)]
+pub struct Level {
+    /// Una matriz 2D que define la estructura del mapa del nivel.
+    /// Cada elemento indica el tipo de celda (pared, espacio vacío, ítem, etc.).
+    pub map: Vec<Vec<Cell>>,
+    /// El número de ítems que el jugador debe recoger para completar el nivel.
+    pub required_items: usize,
+    /// El nombre del nivel, que se muestra en la interfaz de usuario.
+    pub name: String,
+    /// Las parejas de teletransportadores, como posiciones `(x, y)` de sus dos
+    /// extremos. El índice de cada pareja es el que guarda `Cell::Teleporter`.
+    pub teleporters: Vec<TeleportPair>,
+    /// Los enemigos del nivel, declarados en la cabecera.
+    pub enemies: Vec<EnemySpawn>,
+}
+
+impl Level {
+    /// Crea un nuevo nivel con un mapa, número de ítems requeridos y nombre.
+    pub fn new(map: Vec<Vec<Cell>>, required_items: usize, name: &str) -> Self {
+        Self {
+            map,
+            required_items,
+            name: name.to_string(),
+            teleporters: Vec::new(),
+            enemies: Vec::new(),
+        }
+    }
+
+    /// Devuelve el ancho del mapa del nivel.
+    pub fn get_width(&self) -> usize {
+        self.map[0].len()
+    }
+
+    /// Devuelve la altura del mapa del nivel.
+    pub fn get_height(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Intenta recoger un ítem en una posición específica del mapa.
+    /// Devuelve `true` si se recogió un ítem, de lo contrario `false`.
+    pub fn collect_item(&mut self, x: usize, y: usize) -> bool {
+        if y < self.map.len() && x < self.map[0].len() && self.map[y][x].is_collectible() {
+            self.map[y][x] = self.map[y][x].collected();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Intenta recoger una llave en una posición del mapa. Devuelve su color si
+    /// había una.
+    pub fn collect_key(&mut self, x: usize, y: usize) -> Option<KeyColor> {
+        match self.map.get(y).and_then(|row| row.get(x)) {
+            Some(&Cell::Key(color)) => {
+                self.map[y][x] = Cell::Floor;
+                Some(color)
+            }
+            _ => None,
+        }
+    }
+
+    /// Devuelve el otro extremo del teletransportador que hay en una posición
+    /// del mapa, o `None` si allí no hay ninguno.
+    pub fn teleport_destination(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        match self.map.get(y).and_then(|row| row.get(x)) {
+            Some(&Cell::Teleporter(pair)) => {
+                let [a, b] = *self.teleporters.get(pair as usize)?;
+                if a == (x, y) {
+                    Some(b)
+                } else if b == (x, y) {
+                    Some(a)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
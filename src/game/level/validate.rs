@@ -0,0 +1,508 @@
+//! Validación de niveles: comprueba que un nivel sea consistente y se pueda ganar.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::Level;
+use crate::game::{can_step, Cell, Grid, KeyColor};
+
+/// Un problema encontrado al validar un nivel. Las posiciones son coordenadas
+/// `(x, y)` del mapa.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelIssue {
+    /// El mapa no tiene filas o la primera fila está vacía.
+    EmptyMap,
+    /// Una fila no tiene la misma longitud que la primera.
+    RaggedRow { row: usize, expected: usize, found: usize },
+    /// Una celda del borde del mapa no es una pared, por lo que el jugador podría salir del mapa.
+    OpenBorder { x: usize, y: usize },
+    /// El mapa no tiene ninguna casilla de inicio.
+    NoStart,
+    /// El mapa tiene más de una casilla de inicio.
+    MultipleStarts(Vec<(usize, usize)>),
+    /// El mapa no tiene ninguna casilla de meta.
+    NoGoal,
+    /// No se puede llegar a la meta desde el inicio.
+    UnreachableGoal { x: usize, y: usize },
+    /// No se puede llegar al ítem desde el inicio.
+    UnreachableItem { x: usize, y: usize },
+    /// `required_items` no coincide con la cantidad de ítems del mapa.
+    ItemCountMismatch { required: usize, available: usize },
+    /// No hay suficientes ítems alcanzables para completar el nivel.
+    NotEnoughReachableItems { required: usize, reachable: usize },
+    /// Se puede llegar al ítem, pero desde él ya no se puede volver a la meta,
+    /// por ejemplo porque una cinta no deja salir del pasillo en el que está.
+    TrappedItem { x: usize, y: usize },
+    /// Se puede llegar a la llave, pero desde ella ya no se puede volver a la meta.
+    TrappedKey { x: usize, y: usize, color: KeyColor },
+    /// El mapa no tiene ninguna llave del color de la puerta, por lo que nunca se abre.
+    DoorWithoutKey { x: usize, y: usize, color: KeyColor },
+}
+
+impl LevelIssue {
+    /// Indica si el problema impide jugar o ganar el nivel. Los problemas que no
+    /// son errores se consideran advertencias.
+    pub fn is_error(&self) -> bool {
+        match self {
+            // Un ítem que sobra puede dejarse; si faltan ítems, ya se informa
+            // con `NotEnoughReachableItems`.
+            LevelIssue::UnreachableItem { .. } | LevelIssue::TrappedItem { .. } | LevelIssue::DoorWithoutKey { .. } => {
+                false
+            }
+            LevelIssue::ItemCountMismatch { required, available } => required > available,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelIssue::EmptyMap => write!(f, "el mapa está vacío"),
+            LevelIssue::RaggedRow { row, expected, found } => write!(
+                f,
+                "la fila {} tiene {} columnas, pero se esperaban {}",
+                row, found, expected
+            ),
+            LevelIssue::OpenBorder { x, y } => {
+                write!(f, "el borde del mapa está abierto en ({}, {})", x, y)
+            }
+            LevelIssue::NoStart => write!(f, "el mapa no tiene casilla de inicio"),
+            LevelIssue::MultipleStarts(starts) => {
+                write!(f, "el mapa tiene {} casillas de inicio:", starts.len())?;
+                for (x, y) in starts {
+                    write!(f, " ({}, {})", x, y)?;
+                }
+                Ok(())
+            }
+            LevelIssue::NoGoal => write!(f, "el mapa no tiene casilla de meta"),
+            LevelIssue::UnreachableGoal { x, y } => {
+                write!(f, "la meta en ({}, {}) no se puede alcanzar desde el inicio", x, y)
+            }
+            LevelIssue::UnreachableItem { x, y } => {
+                write!(f, "el ítem en ({}, {}) no se puede alcanzar desde el inicio", x, y)
+            }
+            LevelIssue::ItemCountMismatch { required, available } => write!(
+                f,
+                "el nivel requiere {} ítems, pero el mapa tiene {}",
+                required, available
+            ),
+            LevelIssue::NotEnoughReachableItems { required, reachable } => write!(
+                f,
+                "el nivel requiere {} ítems, pero solo {} se pueden alcanzar",
+                required, reachable
+            ),
+            LevelIssue::TrappedItem { x, y } => write!(
+                f,
+                "el ítem en ({}, {}) se puede alcanzar, pero desde él no se vuelve a la meta",
+                x, y
+            ),
+            LevelIssue::TrappedKey { x, y, color } => write!(
+                f,
+                "la llave {} en ({}, {}) se puede alcanzar, pero desde ella no se vuelve a la meta",
+                color.name(),
+                x,
+                y
+            ),
+            LevelIssue::DoorWithoutKey { x, y, color } => write!(
+                f,
+                "la puerta {} en ({}, {}) no tiene ninguna llave en el mapa",
+                color.name(),
+                x,
+                y
+            ),
+        }
+    }
+}
+
+/// El resultado de validar un nivel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Todos los problemas encontrados, errores y advertencias.
+    pub issues: Vec<LevelIssue>,
+}
+
+impl ValidationReport {
+    /// Indica si el nivel no tiene errores (puede tener advertencias).
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Devuelve los problemas que impiden jugar o ganar el nivel.
+    pub fn errors(&self) -> impl Iterator<Item = &LevelIssue> {
+        self.issues.iter().filter(|issue| issue.is_error())
+    }
+
+    /// Devuelve los problemas que no impiden ganar el nivel.
+    pub fn warnings(&self) -> impl Iterator<Item = &LevelIssue> {
+        self.issues.iter().filter(|issue| !issue.is_error())
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let label = if issue.is_error() { "error" } else { "advertencia" };
+            write!(f, "{}: {}", label, issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl Level {
+    /// Comprueba que el nivel sea consistente y se pueda completar: que el mapa
+    /// sea rectangular y cerrado, que haya un único inicio y que la meta y
+    /// suficientes ítems sean alcanzables desde él, abriendo por el camino las
+    /// puertas cuyas llaves se puedan recoger, usando los teletransportadores y
+    /// respetando el sentido de las cintas. Los ítems y las llaves desde los que
+    /// ya no se puede volver a la meta se marcan como trampas.
+    pub fn validate(&self) -> ValidationReport {
+        let mut issues = Vec::new();
+
+        let width = match self.map.first() {
+            Some(row) if !row.is_empty() => row.len(),
+            _ => {
+                issues.push(LevelIssue::EmptyMap);
+                return ValidationReport { issues };
+            }
+        };
+        let height = self.map.len();
+
+        for (y, row) in self.map.iter().enumerate() {
+            if row.len() != width {
+                issues.push(LevelIssue::RaggedRow {
+                    row: y,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+        }
+        if !issues.is_empty() {
+            // El resto de comprobaciones asume un mapa rectangular.
+            return ValidationReport { issues };
+        }
+
+        let cells = || {
+            self.map
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &cell)| (x, y, cell)))
+        };
+
+        for (x, y, cell) in cells() {
+            let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            if on_border && !cell.is_wall() {
+                issues.push(LevelIssue::OpenBorder { x, y });
+            }
+        }
+
+        let starts: Vec<_> = cells()
+            .filter(|&(_, _, cell)| cell == Cell::Start)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let goals: Vec<_> = cells()
+            .filter(|&(_, _, cell)| cell == Cell::Goal)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let items: Vec<_> = cells()
+            .filter(|&(_, _, cell)| cell.is_collectible())
+            .map(|(x, y, _)| (x, y))
+            .collect();
+
+        for (x, y, cell) in cells() {
+            if let Cell::Door { color, .. } = cell {
+                if !cells().any(|(_, _, other)| other == Cell::Key(color)) {
+                    issues.push(LevelIssue::DoorWithoutKey { x, y, color });
+                }
+            }
+        }
+
+        match starts.len() {
+            0 => issues.push(LevelIssue::NoStart),
+            1 => {}
+            _ => issues.push(LevelIssue::MultipleStarts(starts.clone())),
+        }
+        if goals.is_empty() {
+            issues.push(LevelIssue::NoGoal);
+        }
+        if self.required_items != items.len() {
+            issues.push(LevelIssue::ItemCountMismatch {
+                required: self.required_items,
+                available: items.len(),
+            });
+        }
+
+        if let Some(&start) = starts.first() {
+            let (reachable, keys) = self.reachable_from(start);
+
+            let goal_reachable = goals.iter().any(|&(x, y)| reachable[y][x]);
+            if !goal_reachable {
+                for &(x, y) in &goals {
+                    issues.push(LevelIssue::UnreachableGoal { x, y });
+                }
+            }
+
+            // Si la meta no se alcanza, todo sería una trampa: no se marca nada.
+            let escapes = goal_reachable.then(|| self.reaching(&goals, passable_with(&keys)));
+            let trapped = |x: usize, y: usize| escapes.as_ref().is_some_and(|escapes| !escapes[y][x]);
+
+            let mut reachable_items = 0;
+            for &(x, y) in &items {
+                if !reachable[y][x] {
+                    issues.push(LevelIssue::UnreachableItem { x, y });
+                } else if trapped(x, y) {
+                    issues.push(LevelIssue::TrappedItem { x, y });
+                } else {
+                    reachable_items += 1;
+                }
+            }
+            if reachable_items < self.required_items && reachable_items < items.len() {
+                issues.push(LevelIssue::NotEnoughReachableItems {
+                    required: self.required_items,
+                    reachable: reachable_items,
+                });
+            }
+
+            for (x, y, cell) in cells() {
+                if let Cell::Key(color) = cell {
+                    if reachable[y][x] && trapped(x, y) {
+                        issues.push(LevelIssue::TrappedKey { x, y, color });
+                    }
+                }
+            }
+        }
+
+        ValidationReport { issues }
+    }
+
+    /// Marca las celdas alcanzables desde `start` y devuelve también las llaves
+    /// que se pueden recoger. Las llaves no se gastan, así que basta con
+    /// repetir la búsqueda abriendo las puertas de las llaves alcanzadas hasta
+    /// que no aparezca ninguna llave nueva.
+    fn reachable_from(&self, start: (usize, usize)) -> (Vec<Vec<bool>>, Vec<KeyColor>) {
+        let mut keys: Vec<KeyColor> = Vec::new();
+        loop {
+            let reachable = self.flood_fill(start, passable_with(&keys));
+
+            let mut found_key = false;
+            for (y, row) in self.map.iter().enumerate() {
+                for (x, &cell) in row.iter().enumerate() {
+                    if let Cell::Key(color) = cell {
+                        if reachable[y][x] && !keys.contains(&color) {
+                            keys.push(color);
+                            found_key = true;
+                        }
+                    }
+                }
+            }
+            if !found_key {
+                return (reachable, keys);
+            }
+        }
+    }
+
+    /// Marca las celdas desde las que se puede llegar a alguna de `targets`: la
+    /// búsqueda en anchura de [`Level::flood_fill`] hecha hacia atrás. A una
+    /// celda se llega desde sus vecinas que pueden dar el paso con `can_step`,
+    /// y al extremo de un teletransportador, desde las vecinas del otro extremo.
+    fn reaching(&self, targets: &[(usize, usize)], passable: impl Fn(Cell) -> bool) -> Vec<Vec<bool>> {
+        let (width, height) = (self.get_width(), self.get_height());
+        let mut marked = vec![vec![false; width]; height];
+        let mut queue = VecDeque::new();
+        for &(x, y) in targets {
+            marked[y][x] = true;
+            queue.push_back((x, y));
+        }
+
+        while let Some((x, y)) = queue.pop_front() {
+            // La celda en la que hay que entrar para acabar en (x, y).
+            let entered = self.teleport_destination(x, y).unwrap_or((x, y));
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let from = (entered.0.wrapping_add_signed(dx), entered.1.wrapping_add_signed(dy));
+                if from.0 < width
+                    && from.1 < height
+                    && !marked[from.1][from.0]
+                    && can_step(&self.map, from, entered, &passable)
+                {
+                    marked[from.1][from.0] = true;
+                    queue.push_back(from);
+                }
+            }
+        }
+        marked
+    }
+
+    /// Marca las celdas alcanzables desde `start` caminando entre celdas
+    /// vecinas por las que `passable` deja pasar (búsqueda en anchura). Las
+    /// cintas solo se recorren en su sentido, y entrar en un teletransportador
+    /// deja al jugador en el otro extremo de su pareja.
+    fn flood_fill(&self, start: (usize, usize), passable: impl Fn(Cell) -> bool) -> Vec<Vec<bool>> {
+        let grid = Grid::new(self.map[0].len(), self.map.len(), |from, to| {
+            can_step(&self.map, from, to, &passable)
+        })
+        .with_warps(|(x, y)| self.teleport_destination(x, y));
+
+        grid.bfs(start)
+            .into_iter()
+            .map(|row| row.into_iter().map(|steps| steps.is_some()).collect())
+            .collect()
+    }
+}
+
+/// La regla de paso del validador: las paredes no se atraviesan nunca y las
+/// puertas solo si ya se tiene su llave.
+fn passable_with(keys: &[KeyColor]) -> impl Fn(Cell) -> bool + '_ {
+    move |cell| match cell {
+        Cell::Door { color, .. } => keys.contains(&color),
+        _ => !cell.is_solid(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// Crea un nivel a partir de sus filas de glifos.
+    fn level(required_items: usize, rows: &[&str]) -> Level {
+        let map = rows
+            .iter()
+            .map(|row| row.chars().map(|glyph| Cell::from_glyph(glyph).unwrap()).collect())
+            .collect();
+        Level::new(map, required_items, "Prueba")
+    }
+
+    fn issues(required_items: usize, rows: &[&str]) -> Vec<LevelIssue> {
+        level(required_items, rows).validate().issues
+    }
+
+    #[test]
+    fn a_winnable_level_has_no_issues() {
+        let report = level(1, &["#######", "#S*.rR#", "#####G#", "#######"]).validate();
+        assert!(report.issues.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn malformed_maps_stop_the_validation() {
+        assert_eq!(Level::new(Vec::new(), 0, "Prueba").validate().issues, vec![LevelIssue::EmptyMap]);
+        assert_eq!(
+            issues(0, &["####", "#SG", "####"]),
+            vec![LevelIssue::RaggedRow { row: 1, expected: 4, found: 3 }]
+        );
+    }
+
+    #[test]
+    fn reports_open_borders_and_missing_or_repeated_cells() {
+        assert_eq!(issues(0, &["####", "#SG.", "####"]), vec![LevelIssue::OpenBorder { x: 3, y: 1 }]);
+        assert_eq!(issues(0, &["####", "#..#", "####"]), vec![LevelIssue::NoStart, LevelIssue::NoGoal]);
+        assert_eq!(
+            issues(0, &["#####", "#SSG#", "#####"]),
+            vec![LevelIssue::MultipleStarts(vec![(1, 1), (2, 1)])]
+        );
+    }
+
+    #[test]
+    fn reports_what_cannot_be_reached() {
+        assert_eq!(issues(0, &["#####", "#S#G#", "#####"]), vec![LevelIssue::UnreachableGoal { x: 3, y: 1 }]);
+        // La llave está detrás de su propia puerta, así que la meta no se alcanza.
+        assert_eq!(
+            issues(0, &["#######", "#S.RrG#", "#######"]),
+            vec![LevelIssue::UnreachableGoal { x: 5, y: 1 }]
+        );
+
+        let report = level(1, &["#######", "#S*G#*#", "#######"]).validate();
+        assert_eq!(
+            report.issues,
+            vec![
+                LevelIssue::ItemCountMismatch { required: 1, available: 2 },
+                LevelIssue::UnreachableItem { x: 5, y: 1 },
+            ]
+        );
+        assert!(report.is_valid());
+
+        let report = level(2, &["#######", "#S*G#*#", "#######"]).validate();
+        assert_eq!(
+            report.issues,
+            vec![
+                LevelIssue::UnreachableItem { x: 5, y: 1 },
+                LevelIssue::NotEnoughReachableItems { required: 2, reachable: 1 },
+            ]
+        );
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn reports_items_and_keys_behind_a_conveyor_trap() {
+        // La cinta lleva al ítem, pero no deja volver a la meta, que está detrás del inicio.
+        let report = level(1, &["######", "#GS>*#", "######"]).validate();
+        assert_eq!(
+            report.issues,
+            vec![
+                LevelIssue::TrappedItem { x: 4, y: 1 },
+                LevelIssue::NotEnoughReachableItems { required: 1, reachable: 0 },
+            ]
+        );
+        assert!(!report.is_valid());
+
+        // Si el ítem atrapado sobra, el nivel se puede ganar sin recogerlo.
+        let report = level(1, &["#######", "#*GS>*#", "#######"]).validate();
+        assert_eq!(
+            report.issues,
+            vec![
+                LevelIssue::ItemCountMismatch { required: 1, available: 2 },
+                LevelIssue::TrappedItem { x: 5, y: 1 },
+            ]
+        );
+        assert!(report.is_valid());
+
+        let report = level(0, &["######", "#GS>r#", "######"]).validate();
+        assert_eq!(report.issues, vec![LevelIssue::TrappedKey { x: 4, y: 1, color: KeyColor::Red }]);
+
+        // Con un teletransportador de vuelta, la trampa desaparece.
+        let mut level = level(1, &["#######", "#GTS>*#", "#####T#", "#######"]);
+        level.teleporters = vec![[(2, 1), (5, 2)]];
+        assert!(level.validate().issues.is_empty(), "{}", level.validate());
+    }
+
+    #[test]
+    fn item_count_mismatch_is_an_error_only_when_too_few_items() {
+        let report = level(2, &["#####", "#S*G#", "#####"]).validate();
+        assert_eq!(report.issues, vec![LevelIssue::ItemCountMismatch { required: 2, available: 1 }]);
+        assert!(!report.is_valid());
+
+        let report = level(0, &["#####", "#S*G#", "#####"]).validate();
+        assert_eq!(report.issues, vec![LevelIssue::ItemCountMismatch { required: 0, available: 1 }]);
+        assert!(report.is_valid());
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[test]
+    fn a_door_without_key_is_a_warning() {
+        let report = level(0, &["######", "#SGR.#", "######"]).validate();
+        assert_eq!(
+            report.issues,
+            vec![LevelIssue::DoorWithoutKey { x: 3, y: 1, color: KeyColor::Red }]
+        );
+        assert!(report.is_valid());
+        assert_eq!(report.to_string(), "advertencia: la puerta roja en (3, 1) no tiene ninguna llave en el mapa");
+    }
+
+    #[test]
+    fn desafio_needs_only_the_items_it_has() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("levels/03_desafio.txt");
+        let source = std::fs::read_to_string(path).unwrap();
+        let level = Level::parse(&source).unwrap();
+        assert!(level.validate().issues.is_empty(), "{}", level.validate());
+
+        // Antes pedía 8 ítems, pero el mapa solo tiene 7: no se podía ganar.
+        let old = Level::parse(&source.replace("required_items: 7", "required_items: 8")).unwrap();
+        assert_eq!(
+            old.validate().errors().cloned().collect::<Vec<_>>(),
+            vec![LevelIssue::ItemCountMismatch { required: 8, available: 7 }]
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! Generación procedural de laberintos a partir de una semilla.
+//!
+//! Los laberintos se construyen sobre una cuadrícula de "habitaciones" de
+//! `width × height`. Cada habitación ocupa una celda impar del mapa y las paredes
+//! entre habitaciones se derriban según el algoritmo elegido, por lo que el mapa
+//! resultante mide `(2 * width + 1) × (2 * height + 1)` celdas.
+
+use crate::game::{Cell, Grid, Level};
+use crate::math::Rng;
+
+/// Tamaño máximo, en habitaciones por lado, de un laberinto. Los tamaños mayores
+/// se recortan, porque el mapa se reserva entero al generarlo.
+pub const MAX_MAZE_SIZE: usize = 100;
+
+/// Probabilidad de eliminar cada callejón sin salida en los laberintos trenzados.
+const BRAID_PROBABILITY: f32 = 0.8;
+
+/// Enumera los algoritmos de generación de laberintos disponibles.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MazeAlgorithm {
+    /// Búsqueda en profundidad con retroceso: pasillos largos y sinuosos.
+    Backtracker,
+    /// Prim aleatorio: muchas ramificaciones cortas.
+    Prim,
+    /// Kruskal aleatorio: estructura uniforme sin dirección dominante.
+    Kruskal,
+    /// Algoritmo de Eller: se construye fila por fila.
+    Eller,
+    /// Laberinto trenzado: sin casi callejones sin salida y con ciclos.
+    Braid,
+}
+
+impl MazeAlgorithm {
+    /// Todos los algoritmos disponibles.
+    pub const ALL: [MazeAlgorithm; 5] = [
+        MazeAlgorithm::Backtracker,
+        MazeAlgorithm::Prim,
+        MazeAlgorithm::Kruskal,
+        MazeAlgorithm::Eller,
+        MazeAlgorithm::Braid,
+    ];
+
+    /// Devuelve el nombre del algoritmo que se usa en la línea de comandos.
+    pub fn name(self) -> &'static str {
+        match self {
+            MazeAlgorithm::Backtracker => "backtracker",
+            MazeAlgorithm::Prim => "prim",
+            MazeAlgorithm::Kruskal => "kruskal",
+            MazeAlgorithm::Eller => "eller",
+            MazeAlgorithm::Braid => "braid",
+        }
+    }
+
+    /// Busca un algoritmo por su nombre.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|algorithm| algorithm.name() == name)
+    }
+}
+
+/// Parámetros para generar un laberinto.
+#[derive(Clone, Copy, Debug)]
+pub struct MazeConfig {
+    /// La semilla del generador; la misma configuración produce siempre el mismo nivel.
+    pub seed: u64,
+    /// Ancho del laberinto en habitaciones.
+    pub width: usize,
+    /// Alto del laberinto en habitaciones.
+    pub height: usize,
+    /// Cantidad de ítems a colocar.
+    pub items: usize,
+    /// El algoritmo a usar; si es `None`, se elige según la semilla.
+    pub algorithm: Option<MazeAlgorithm>,
+}
+
+impl MazeConfig {
+    /// Crea una configuración con una cantidad de ítems proporcional al tamaño.
+    /// Cada lado se ajusta al rango de 2 a `MAX_MAZE_SIZE` habitaciones.
+    pub fn new(seed: u64, width: usize, height: usize) -> Self {
+        let width = width.clamp(2, MAX_MAZE_SIZE);
+        let height = height.clamp(2, MAX_MAZE_SIZE);
+        Self {
+            seed,
+            width,
+            height,
+            items: (width * height / 10).clamp(1, 12),
+            algorithm: None,
+        }
+    }
+
+    /// Genera el nivel descrito por esta configuración.
+    pub fn generate(&self) -> Level {
+        let mut rng = Rng::new(self.seed);
+        let algorithm = self
+            .algorithm
+            .unwrap_or_else(|| *rng.choose(&MazeAlgorithm::ALL));
+
+        let mut maze = Maze::new(self.width.clamp(2, MAX_MAZE_SIZE), self.height.clamp(2, MAX_MAZE_SIZE));
+        match algorithm {
+            MazeAlgorithm::Backtracker => maze.carve_backtracker(&mut rng),
+            MazeAlgorithm::Prim => maze.carve_prim(&mut rng),
+            MazeAlgorithm::Kruskal => maze.carve_kruskal(&mut rng),
+            MazeAlgorithm::Eller => maze.carve_eller(&mut rng),
+            MazeAlgorithm::Braid => {
+                maze.carve_backtracker(&mut rng);
+                maze.braid(&mut rng, BRAID_PROBABILITY);
+            }
+        }
+
+        let items = maze.place_objectives(&mut rng, self.items);
+        let name = format!(
+            "Aleatorio {}x{} ({}, semilla {})",
+            maze.width,
+            maze.height,
+            algorithm.name(),
+            self.seed
+        );
+
+        Level::new(maze.map, items, &name)
+    }
+}
+
+/// Laberinto en construcción: la cuadrícula de habitaciones y el mapa resultante.
+struct Maze {
+    width: usize,
+    height: usize,
+    map: Vec<Vec<Cell>>,
+}
+
+impl Maze {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            map: vec![vec![Cell::Wall; width * 2 + 1]; height * 2 + 1],
+        }
+    }
+
+    /// Devuelve la posición en el mapa de una habitación.
+    fn to_map((x, y): (usize, usize)) -> (usize, usize) {
+        (x * 2 + 1, y * 2 + 1)
+    }
+
+    fn open_room(&mut self, room: (usize, usize)) {
+        let (x, y) = Self::to_map(room);
+        self.map[y][x] = Cell::Floor;
+    }
+
+    /// Derriba la pared entre dos habitaciones vecinas.
+    fn connect(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let (ax, ay) = Self::to_map(a);
+        let (bx, by) = Self::to_map(b);
+        self.map[ay][ax] = Cell::Floor;
+        self.map[by][bx] = Cell::Floor;
+        self.map[(ay + by) / 2][(ax + bx) / 2] = Cell::Floor;
+    }
+
+    fn is_connected(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let (ax, ay) = Self::to_map(a);
+        let (bx, by) = Self::to_map(b);
+        !self.map[(ay + by) / 2][(ax + bx) / 2].is_solid()
+    }
+
+    fn neighbors(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut result = Vec::with_capacity(4);
+        if x > 0 {
+            result.push((x - 1, y));
+        }
+        if x + 1 < self.width {
+            result.push((x + 1, y));
+        }
+        if y > 0 {
+            result.push((x, y - 1));
+        }
+        if y + 1 < self.height {
+            result.push((x, y + 1));
+        }
+        result
+    }
+
+    fn rooms(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+    }
+
+    /// Búsqueda en profundidad con retroceso usando una pila explícita.
+    fn carve_backtracker(&mut self, rng: &mut Rng) {
+        let mut visited = vec![vec![false; self.width]; self.height];
+        let start = (rng.range(self.width), rng.range(self.height));
+        let mut stack = vec![start];
+        visited[start.1][start.0] = true;
+        self.open_room(start);
+
+        while let Some(&current) = stack.last() {
+            let unvisited: Vec<_> = self
+                .neighbors(current)
+                .into_iter()
+                .filter(|&(x, y)| !visited[y][x])
+                .collect();
+
+            if unvisited.is_empty() {
+                stack.pop();
+                continue;
+            }
+
+            let next = *rng.choose(&unvisited);
+            visited[next.1][next.0] = true;
+            self.connect(current, next);
+            stack.push(next);
+        }
+    }
+
+    /// Prim aleatorio: crece el laberinto desde una habitación eligiendo
+    /// habitaciones de la frontera al azar.
+    fn carve_prim(&mut self, rng: &mut Rng) {
+        let mut in_maze = vec![vec![false; self.width]; self.height];
+        let mut in_frontier = vec![vec![false; self.width]; self.height];
+        let mut frontier = Vec::new();
+
+        let start = (rng.range(self.width), rng.range(self.height));
+        in_maze[start.1][start.0] = true;
+        self.open_room(start);
+        for (x, y) in self.neighbors(start) {
+            in_frontier[y][x] = true;
+            frontier.push((x, y));
+        }
+
+        while !frontier.is_empty() {
+            let room = frontier.swap_remove(rng.range(frontier.len()));
+            let (connected, outside): (Vec<_>, Vec<_>) = self
+                .neighbors(room)
+                .into_iter()
+                .partition(|&(x, y)| in_maze[y][x]);
+
+            self.connect(room, *rng.choose(&connected));
+            in_maze[room.1][room.0] = true;
+
+            for (x, y) in outside {
+                if !in_frontier[y][x] {
+                    in_frontier[y][x] = true;
+                    frontier.push((x, y));
+                }
+            }
+        }
+    }
+
+    /// Kruskal aleatorio: une habitaciones de conjuntos distintos recorriendo
+    /// las paredes en orden aleatorio.
+    fn carve_kruskal(&mut self, rng: &mut Rng) {
+        let width = self.width;
+        let index = |(x, y): (usize, usize)| y * width + x;
+        let mut parent: Vec<usize> = (0..self.width * self.height).collect();
+
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        let mut walls = Vec::new();
+        let rooms: Vec<_> = self.rooms().collect();
+        for room in rooms {
+            self.open_room(room);
+            let (x, y) = room;
+            if x + 1 < self.width {
+                walls.push((room, (x + 1, y)));
+            }
+            if y + 1 < self.height {
+                walls.push((room, (x, y + 1)));
+            }
+        }
+        rng.shuffle(&mut walls);
+
+        for (a, b) in walls {
+            let root_a = find(&mut parent, index(a));
+            let root_b = find(&mut parent, index(b));
+            if root_a != root_b {
+                parent[root_a] = root_b;
+                self.connect(a, b);
+            }
+        }
+    }
+
+    /// Algoritmo de Eller: genera el laberinto fila por fila manteniendo
+    /// conjuntos de habitaciones conectadas.
+    fn carve_eller(&mut self, rng: &mut Rng) {
+        let mut sets = vec![0usize; self.width];
+        let mut next_set = 1;
+
+        for y in 0..self.height {
+            let last_row = y + 1 == self.height;
+
+            for set in sets.iter_mut().filter(|set| **set == 0) {
+                *set = next_set;
+                next_set += 1;
+            }
+            for x in 0..self.width {
+                self.open_room((x, y));
+            }
+
+            // Uniones horizontales; en la última fila se unen todos los conjuntos.
+            for x in 0..self.width - 1 {
+                if sets[x] != sets[x + 1] && (last_row || rng.chance(0.5)) {
+                    let (old, new) = (sets[x + 1], sets[x]);
+                    for set in sets.iter_mut().filter(|set| **set == old) {
+                        *set = new;
+                    }
+                    self.connect((x, y), (x + 1, y));
+                }
+            }
+
+            if last_row {
+                break;
+            }
+
+            // Uniones verticales: cada conjunto baja al menos por una habitación.
+            let mut next_row = vec![0usize; self.width];
+            let mut distinct: Vec<usize> = sets.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+
+            for set in distinct {
+                let mut members: Vec<usize> = (0..self.width).filter(|&x| sets[x] == set).collect();
+                rng.shuffle(&mut members);
+                let count = 1 + rng.range(members.len());
+                for &x in &members[..count] {
+                    self.connect((x, y), (x, y + 1));
+                    next_row[x] = set;
+                }
+            }
+
+            sets = next_row;
+        }
+    }
+
+    /// Elimina callejones sin salida abriendo paredes hacia habitaciones vecinas,
+    /// lo que crea ciclos en el laberinto.
+    fn braid(&mut self, rng: &mut Rng, probability: f32) {
+        let mut rooms: Vec<_> = self.rooms().collect();
+        rng.shuffle(&mut rooms);
+
+        for room in rooms {
+            if !self.is_dead_end(room) || !rng.chance(probability) {
+                continue;
+            }
+
+            let closed: Vec<_> = self
+                .neighbors(room)
+                .into_iter()
+                .filter(|&other| !self.is_connected(room, other))
+                .collect();
+            let dead_ends: Vec<_> = closed
+                .iter()
+                .copied()
+                .filter(|&other| self.is_dead_end(other))
+                .collect();
+
+            let candidates = if dead_ends.is_empty() { closed } else { dead_ends };
+            if !candidates.is_empty() {
+                let other = *rng.choose(&candidates);
+                self.connect(room, other);
+            }
+        }
+    }
+
+    fn is_dead_end(&self, room: (usize, usize)) -> bool {
+        self.neighbors(room)
+            .into_iter()
+            .filter(|&other| self.is_connected(room, other))
+            .count()
+            == 1
+    }
+
+    /// Distancia en pasos desde `from` a cada celda transitable del mapa.
+    fn distances(&self, from: (usize, usize)) -> Vec<Vec<Option<usize>>> {
+        Grid::walkable(&self.map).bfs(from)
+    }
+
+    /// Coloca el inicio, la meta y los ítems en habitaciones bien repartidas:
+    /// el inicio y la meta en extremos opuestos del laberinto y cada ítem en la
+    /// habitación más alejada de todo lo colocado antes. Devuelve la cantidad de
+    /// ítems colocados.
+    fn place_objectives(&mut self, rng: &mut Rng, items: usize) -> usize {
+        let rooms: Vec<_> = self.rooms().map(Self::to_map).collect();
+        let farthest = |dist: &Vec<Vec<Option<usize>>>| {
+            rooms
+                .iter()
+                .copied()
+                .max_by_key(|&(x, y)| dist[y][x].unwrap_or(0))
+                .unwrap_or(rooms[0])
+        };
+
+        // El inicio es el extremo más lejano desde una habitación al azar y la
+        // meta el más lejano desde el inicio, aproximando el diámetro del laberinto.
+        let probe = *rng.choose(&rooms);
+        let start = farthest(&self.distances(probe));
+        let start_dist = self.distances(start);
+        let goal = farthest(&start_dist);
+        let goal_dist = self.distances(goal);
+
+        self.map[start.1][start.0] = Cell::Start;
+        self.map[goal.1][goal.0] = Cell::Goal;
+
+        let mut nearest: Vec<Vec<usize>> = start_dist
+            .iter()
+            .zip(&goal_dist)
+            .map(|(a, b)| {
+                a.iter()
+                    .zip(b)
+                    .map(|(a, b)| a.unwrap_or(0).min(b.unwrap_or(0)))
+                    .collect()
+            })
+            .collect();
+
+        let mut placed = 0;
+        while placed < items {
+            let candidate = rooms
+                .iter()
+                .copied()
+                .filter(|&(x, y)| self.map[y][x] == Cell::Floor)
+                .max_by_key(|&(x, y)| nearest[y][x]);
+            let Some((x, y)) = candidate else { break };
+
+            self.map[y][x] = Cell::Item;
+            placed += 1;
+
+            for (row, dist_row) in nearest.iter_mut().zip(self.distances((x, y))) {
+                for (n, d) in row.iter_mut().zip(dist_row) {
+                    *n = (*n).min(d.unwrap_or(0));
+                }
+            }
+        }
+
+        placed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64, algorithm: MazeAlgorithm) -> MazeConfig {
+        MazeConfig {
+            algorithm: Some(algorithm),
+            ..MazeConfig::new(seed, 9, 7)
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_maze() {
+        for algorithm in MazeAlgorithm::ALL {
+            let first = config(42, algorithm).generate();
+            let second = config(42, algorithm).generate();
+            assert!(first.map == second.map, "{} no es reproducible", algorithm.name());
+            assert_eq!(first.name, second.name);
+        }
+
+        let first = MazeConfig::new(7, 9, 7).generate();
+        assert!(first.map == MazeConfig::new(7, 9, 7).generate().map);
+        assert!(first.map != MazeConfig::new(8, 9, 7).generate().map);
+    }
+
+    #[test]
+    fn sizes_are_clamped_to_the_limits() {
+        let huge = MazeConfig::new(1, usize::MAX / 2, 50_000);
+        assert_eq!((huge.width, huge.height), (MAX_MAZE_SIZE, MAX_MAZE_SIZE));
+        let tiny = MazeConfig::new(1, 0, 1);
+        assert_eq!((tiny.width, tiny.height), (2, 2));
+
+        // Los campos son públicos, así que `generate` también recorta.
+        let level = MazeConfig { width: usize::MAX, ..huge }.generate();
+        assert_eq!(level.get_width(), MAX_MAZE_SIZE * 2 + 1);
+    }
+
+    #[test]
+    fn every_generated_maze_is_solvable() {
+        for algorithm in MazeAlgorithm::ALL {
+            for seed in 0..40 {
+                let level = config(seed, algorithm).generate();
+                assert_eq!(level.get_width(), 19);
+                assert_eq!(level.get_height(), 15);
+                let report = level.validate();
+                assert!(
+                    report.issues.is_empty(),
+                    "{} con semilla {}:\n{}",
+                    algorithm.name(),
+                    seed,
+                    report
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn algorithm_names_round_trip() {
+        for algorithm in MazeAlgorithm::ALL {
+            assert_eq!(MazeAlgorithm::from_name(algorithm.name()), Some(algorithm));
+        }
+        assert_eq!(MazeAlgorithm::from_name("laberinto"), None);
+    }
+}
@@ -1,15 +1,33 @@
 //! El módulo `game` contiene la lógica principal y las estructuras de datos del juego.
 
 // Declaración de los submódulos que componen el módulo de juego.
+pub mod action;
 pub mod camera;
+pub mod cell;
+pub mod data;
+pub mod enemy;
 pub mod entity;
+pub mod keymap;
 pub mod player;
 pub mod state;
 pub mod level;
+pub mod maze;
+pub mod pack;
+pub mod path;
+pub mod runner;
 
 // Exporta las estructuras y enums más importantes para que sean accesibles desde otros módulos.
+pub use action::*;
 pub use camera::*;
+pub use cell::*;
+pub use data::*;
+pub use enemy::*;
 pub use entity::*;
+pub use keymap::*;
 pub use player::*;
 pub use state::*;
 pub use level::*;
+pub use maze::*;
+pub use pack::*;
+pub use path::*;
+pub use runner::*;
@@ -0,0 +1,481 @@
+//! Paquetes de niveles: directorios con un manifiesto que define qué niveles se
+//! juegan, en qué orden, con qué nombre y cuándo se desbloquean.
+//!
+//! El manifiesto es un archivo `pack.txt` dentro del directorio del paquete:
+//!
+//! ```text
+//! # Las líneas que empiezan con `#` son comentarios.
+//! name: Clásico
+//!
+//! level: 01_comienzo.txt | Nivel 1: El Comienzo | open
+//! level: 02_laberinto.txt | Nivel 2: El Laberinto | after 1
+//! level: 03_desafio.txt | | completed 2
+//! ```
+//!
+//! Cada entrada `level` indica el archivo del nivel, un nombre opcional que
+//! reemplaza al de la cabecera del nivel y una regla de desbloqueo opcional
+//! (`open` por defecto). Si el directorio no tiene manifiesto, se usan todos los
+//! archivos `.txt` ordenados por nombre y todos quedan desbloqueados.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::game::{data_paths, Level, LevelLoadError};
+
+/// Nombre del archivo de manifiesto dentro de un directorio de paquete.
+pub const MANIFEST_FILE: &str = "pack.txt";
+
+/// Nombre del directorio de niveles que se busca por defecto.
+pub const DEFAULT_PACK_DIR: &str = "levels";
+
+/// Condición que debe cumplirse para poder jugar un nivel del paquete.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UnlockRule {
+    /// El nivel está disponible desde el principio.
+    Open,
+    /// El nivel se desbloquea al completar el nivel indicado (base 1).
+    After(usize),
+    /// El nivel se desbloquea al completar al menos esa cantidad de niveles.
+    Completed(usize),
+}
+
+impl UnlockRule {
+    /// Comprueba si la regla se cumple según los niveles completados.
+    pub fn is_satisfied(&self, completed: &[bool]) -> bool {
+        match *self {
+            UnlockRule::Open => true,
+            UnlockRule::After(level) => level
+                .checked_sub(1)
+                .and_then(|index| completed.get(index))
+                .copied()
+                .unwrap_or(false),
+            UnlockRule::Completed(count) => completed.iter().filter(|&&done| done).count() >= count,
+        }
+    }
+
+    /// Describe la regla para mostrarla en la selección de nivel.
+    pub fn describe(&self) -> String {
+        match *self {
+            UnlockRule::Open => "disponible".to_string(),
+            UnlockRule::After(level) => format!("completa el nivel {}", level),
+            UnlockRule::Completed(count) => format!("completa {} niveles", count),
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut words = text.split_whitespace();
+        let rule = match (words.next()?, words.next()) {
+            ("open", None) => UnlockRule::Open,
+            ("after", Some(n)) => UnlockRule::After(n.parse().ok().filter(|&n| n > 0)?),
+            ("completed", Some(n)) => UnlockRule::Completed(n.parse().ok()?),
+            _ => return None,
+        };
+        words.next().is_none().then_some(rule)
+    }
+}
+
+/// Una entrada del manifiesto: un nivel del paquete.
+#[derive(Clone, Debug)]
+pub struct PackEntry {
+    /// Ruta al archivo del nivel.
+    pub file: PathBuf,
+    /// Nombre a mostrar; si no se indica, se usa el de la cabecera del nivel.
+    pub name: Option<String>,
+    /// Regla de desbloqueo del nivel.
+    pub unlock: UnlockRule,
+}
+
+/// Un conjunto ordenado de niveles cargado desde un directorio.
+#[derive(Clone, Debug)]
+pub struct LevelPack {
+    /// El nombre del paquete.
+    pub name: String,
+    /// El directorio del que se cargó el paquete.
+    pub dir: PathBuf,
+    /// Los niveles del paquete, en orden de juego.
+    pub entries: Vec<PackEntry>,
+}
+
+/// Describe un error en una línea del manifiesto.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ManifestErrorKind {
+    /// La línea no tiene la forma `clave: valor`.
+    MalformedLine,
+    /// La línea usa una clave desconocida.
+    UnknownField(String),
+    /// Una entrada `level` no indica el archivo del nivel.
+    MissingFile,
+    /// La regla de desbloqueo no es válida.
+    InvalidUnlock(String),
+    /// La regla de desbloqueo hace referencia a un nivel que no existe o pide
+    /// completar tantos niveles como tiene el paquete (incluido el propio).
+    UnlockOutOfRange(usize),
+    /// La regla `after N` apunta al propio nivel o a uno posterior.
+    UnlockNotEarlier(usize),
+    /// Las reglas del paquete impiden desbloquear el nivel, por ejemplo porque
+    /// ningún nivel está disponible desde el principio.
+    NeverUnlocked,
+}
+
+/// Error producido al buscar o cargar un paquete de niveles.
+#[derive(Debug)]
+pub enum PackError {
+    /// No se pudo leer el directorio o el manifiesto.
+    Io(PathBuf, io::Error),
+    /// El manifiesto contiene un error en la línea indicada (base 1).
+    Manifest {
+        path: PathBuf,
+        line: usize,
+        kind: ManifestErrorKind,
+    },
+    /// El paquete no contiene ningún nivel.
+    Empty(PathBuf),
+    /// No se encontró ningún paquete en los directorios indicados.
+    NotFound(Vec<PathBuf>),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            PackError::Manifest { path, line, kind } => {
+                write!(f, "{}: línea {}: ", path.display(), line)?;
+                match kind {
+                    ManifestErrorKind::MalformedLine => {
+                        write!(f, "se esperaba una línea de la forma `clave: valor`")
+                    }
+                    ManifestErrorKind::UnknownField(field) => {
+                        write!(f, "campo desconocido `{}`", field)
+                    }
+                    ManifestErrorKind::MissingFile => {
+                        write!(f, "la entrada `level` no indica ningún archivo")
+                    }
+                    ManifestErrorKind::InvalidUnlock(rule) => write!(
+                        f,
+                        "regla de desbloqueo inválida `{}` (usa `open`, `after N` o `completed N`)",
+                        rule
+                    ),
+                    ManifestErrorKind::UnlockOutOfRange(n) => write!(
+                        f,
+                        "la regla de desbloqueo usa el número {}, pero el paquete no tiene tantos otros niveles",
+                        n
+                    ),
+                    ManifestErrorKind::UnlockNotEarlier(n) => write!(
+                        f,
+                        "la regla `after {}` debe apuntar a un nivel anterior a este",
+                        n
+                    ),
+                    ManifestErrorKind::NeverUnlocked => {
+                        write!(f, "las reglas de desbloqueo del paquete impiden jugar este nivel")
+                    }
+                }
+            }
+            PackError::Empty(path) => write!(f, "{}: el paquete no contiene niveles", path.display()),
+            PackError::NotFound(dirs) => {
+                write!(f, "no se encontró ningún paquete de niveles en:")?;
+                for dir in dirs {
+                    write!(f, " {}", dir.display())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+impl LevelPack {
+    /// Carga el paquete de un directorio, usando su manifiesto si existe.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, PackError> {
+        let dir = dir.as_ref();
+        let manifest = dir.join(MANIFEST_FILE);
+
+        let pack = if manifest.is_file() {
+            let source = fs::read_to_string(&manifest)
+                .map_err(|err| PackError::Io(manifest.clone(), err))?;
+            Self::parse_manifest(dir, &manifest, &source)?
+        } else {
+            Self::scan(dir)?
+        };
+
+        if pack.entries.is_empty() {
+            return Err(PackError::Empty(dir.to_path_buf()));
+        }
+        Ok(pack)
+    }
+
+    /// Busca un paquete en el directorio indicado o, si no se indica ninguno, en
+    /// los directorios de niveles por defecto.
+    pub fn discover(explicit: Option<&Path>) -> Result<Self, PackError> {
+        if let Some(dir) = explicit {
+            return Self::load(dir);
+        }
+
+        let candidates = default_pack_dirs();
+        match candidates.iter().find(|dir| dir.is_dir()) {
+            Some(dir) => Self::load(dir),
+            None => Err(PackError::NotFound(candidates)),
+        }
+    }
+
+    /// Carga todos los niveles del paquete, aplicando los nombres del manifiesto.
+    pub fn load_levels(&self) -> Result<Vec<Level>, LevelLoadError> {
+        self.entries.iter().map(Self::load_entry).collect()
+    }
+
+    /// Carga solo el nivel `index` (base 0) del paquete, aplicando el nombre del
+    /// manifiesto. Devuelve `None` si el paquete no tiene ese nivel.
+    pub fn load_level(&self, index: usize) -> Option<Result<Level, LevelLoadError>> {
+        self.entries.get(index).map(Self::load_entry)
+    }
+
+    fn load_entry(entry: &PackEntry) -> Result<Level, LevelLoadError> {
+        let mut level = Level::load(&entry.file)?;
+        if let Some(name) = &entry.name {
+            level.name = name.clone();
+        }
+        Ok(level)
+    }
+
+    /// Devuelve la cantidad de niveles del paquete.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Indica si el paquete no tiene niveles.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Indica si el nivel `index` (base 0) está desbloqueado.
+    pub fn is_unlocked(&self, index: usize, completed: &[bool]) -> bool {
+        self.entries
+            .get(index)
+            .is_some_and(|entry| entry.unlock.is_satisfied(completed))
+    }
+
+    fn parse_manifest(dir: &Path, path: &Path, source: &str) -> Result<Self, PackError> {
+        let error = |line: usize, kind: ManifestErrorKind| PackError::Manifest {
+            path: path.to_path_buf(),
+            line,
+            kind,
+        };
+
+        let mut name = None;
+        let mut entries = Vec::new();
+        let mut unlock_lines = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| error(line_no, ManifestErrorKind::MalformedLine))?;
+
+            match key.trim() {
+                "name" => name = Some(value.trim().to_string()),
+                "level" => {
+                    let mut fields = value.split('|').map(str::trim);
+                    let file = fields
+                        .next()
+                        .filter(|file| !file.is_empty())
+                        .ok_or_else(|| error(line_no, ManifestErrorKind::MissingFile))?;
+                    let display_name = fields
+                        .next()
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string);
+                    let unlock = match fields.next().filter(|rule| !rule.is_empty()) {
+                        Some(rule) => UnlockRule::parse(rule).ok_or_else(|| {
+                            error(line_no, ManifestErrorKind::InvalidUnlock(rule.to_string()))
+                        })?,
+                        None => UnlockRule::Open,
+                    };
+
+                    entries.push(PackEntry {
+                        file: dir.join(file),
+                        name: display_name,
+                        unlock,
+                    });
+                    unlock_lines.push(line_no);
+                }
+                other => {
+                    return Err(error(line_no, ManifestErrorKind::UnknownField(other.to_string())));
+                }
+            }
+        }
+
+        // Las reglas `after N` deben apuntar a un nivel anterior, lo que evita
+        // los ciclos, y las `completed N` no pueden contar el propio nivel.
+        for (i, (entry, &line_no)) in entries.iter().zip(&unlock_lines).enumerate() {
+            match entry.unlock {
+                UnlockRule::After(n) if n > entries.len() => {
+                    return Err(error(line_no, ManifestErrorKind::UnlockOutOfRange(n)));
+                }
+                UnlockRule::After(n) if n > i => {
+                    return Err(error(line_no, ManifestErrorKind::UnlockNotEarlier(n)));
+                }
+                UnlockRule::Completed(n) if n >= entries.len() => {
+                    return Err(error(line_no, ManifestErrorKind::UnlockOutOfRange(n)));
+                }
+                _ => {}
+            }
+        }
+
+        // Juega todos los niveles que se van desbloqueando; los que nunca se
+        // desbloquean no se podrán jugar.
+        let mut completed = vec![false; entries.len()];
+        while let Some(next) = (0..entries.len()).find(|&i| !completed[i] && entries[i].unlock.is_satisfied(&completed)) {
+            completed[next] = true;
+        }
+        if let Some(locked) = completed.iter().position(|&done| !done) {
+            return Err(error(unlock_lines[locked], ManifestErrorKind::NeverUnlocked));
+        }
+
+        Ok(Self {
+            name: name.unwrap_or_else(|| dir_name(dir)),
+            dir: dir.to_path_buf(),
+            entries,
+        })
+    }
+
+    fn scan(dir: &Path) -> Result<Self, PackError> {
+        let read_dir = fs::read_dir(dir).map_err(|err| PackError::Io(dir.to_path_buf(), err))?;
+
+        let mut files = Vec::new();
+        for entry in read_dir {
+            let path = entry.map_err(|err| PackError::Io(dir.to_path_buf(), err))?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "txt") {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        Ok(Self {
+            name: dir_name(dir),
+            dir: dir.to_path_buf(),
+            entries: files
+                .into_iter()
+                .map(|file| PackEntry {
+                    file,
+                    name: None,
+                    unlock: UnlockRule::Open,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Devuelve los directorios donde se buscan niveles si no se indica `--pack`.
+pub fn default_pack_dirs() -> Vec<PathBuf> {
+    data_paths(DEFAULT_PACK_DIR)
+}
+
+fn dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| dir.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<LevelPack, PackError> {
+        LevelPack::parse_manifest(Path::new("pack"), Path::new("pack/pack.txt"), source)
+    }
+
+    /// Devuelve la línea y el tipo del error de manifiesto.
+    fn manifest_error(source: &str) -> (usize, ManifestErrorKind) {
+        match parse(source) {
+            Err(PackError::Manifest { line, kind, .. }) => (line, kind),
+            other => panic!("{:?}: se esperaba un error de manifiesto, se obtuvo {:?}", source, other),
+        }
+    }
+
+    #[test]
+    fn parses_entries_and_rules() {
+        let pack = parse(
+            "# Comentario\nname: Clásico\n\nlevel: a.txt | Uno | open\nlevel: b.txt | | after 1\nlevel: c.txt | Tres | completed 2\nlevel: d.txt\n",
+        )
+        .unwrap();
+        assert_eq!(pack.name, "Clásico");
+        assert_eq!(pack.len(), 4);
+        assert_eq!(pack.entries[0].file, Path::new("pack/a.txt"));
+        assert_eq!(pack.entries[0].name.as_deref(), Some("Uno"));
+        assert_eq!(pack.entries[1].name, None);
+        let rules: Vec<_> = pack.entries.iter().map(|entry| entry.unlock).collect();
+        assert_eq!(
+            rules,
+            [UnlockRule::Open, UnlockRule::After(1), UnlockRule::Completed(2), UnlockRule::Open]
+        );
+    }
+
+    #[test]
+    fn unlock_rules_follow_completed_levels() {
+        let pack = parse("level: a.txt\nlevel: b.txt | | after 1\nlevel: c.txt | | completed 2\n").unwrap();
+        assert!(pack.is_unlocked(0, &[false, false, false]));
+        assert!(!pack.is_unlocked(1, &[false, false, false]));
+        assert!(pack.is_unlocked(1, &[true, false, false]));
+        assert!(!pack.is_unlocked(2, &[true, false, false]));
+        assert!(pack.is_unlocked(2, &[true, true, false]));
+        assert!(!pack.is_unlocked(3, &[true, true, true]));
+    }
+
+    #[test]
+    fn after_zero_is_never_satisfied() {
+        assert!(!UnlockRule::After(0).is_satisfied(&[true, true]));
+        assert_eq!(
+            manifest_error("level: a.txt | | after 0"),
+            (1, ManifestErrorKind::InvalidUnlock("after 0".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_rules_that_can_never_be_met() {
+        let cases = [
+            ("level: a.txt\nlevel: b.txt | | after 3", 2, ManifestErrorKind::UnlockOutOfRange(3)),
+            ("level: a.txt\nlevel: b.txt | | after 2", 2, ManifestErrorKind::UnlockNotEarlier(2)),
+            (
+                "level: a.txt\nlevel: b.txt | | after 3\nlevel: c.txt | | after 2",
+                2,
+                ManifestErrorKind::UnlockNotEarlier(3),
+            ),
+            ("level: a.txt\nlevel: b.txt | | completed 2", 2, ManifestErrorKind::UnlockOutOfRange(2)),
+            ("level: a.txt\nlevel: b.txt | | completed 5", 2, ManifestErrorKind::UnlockOutOfRange(5)),
+            (
+                "level: a.txt | | completed 1\nlevel: b.txt | | after 1",
+                1,
+                ManifestErrorKind::NeverUnlocked,
+            ),
+        ];
+        for (source, line, kind) in cases {
+            assert_eq!(manifest_error(source), (line, kind), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        let cases = [
+            ("name Clásico", 1, ManifestErrorKind::MalformedLine),
+            ("\nlevels: a.txt", 2, ManifestErrorKind::UnknownField("levels".to_string())),
+            ("level:  | Uno", 1, ManifestErrorKind::MissingFile),
+            ("level: a.txt | | later", 1, ManifestErrorKind::InvalidUnlock("later".to_string())),
+        ];
+        for (source, line, kind) in cases {
+            assert_eq!(manifest_error(source), (line, kind), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn shipped_pack_loads() {
+        let pack = LevelPack::load(Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_PACK_DIR)).unwrap();
+        assert!(!pack.is_empty());
+        assert_eq!(pack.load_levels().unwrap().len(), pack.len());
+    }
+}
@@ -0,0 +1,553 @@
+//! Búsqueda de caminos sobre la cuadrícula de un mapa: búsqueda en anchura,
+//! A*, mapas de distancias (Dijkstra) y campos de flujo.
+//!
+//! Todas las búsquedas trabajan sobre una [`Grid`], que decide con una función
+//! qué pasos entre celdas vecinas se pueden dar, de modo que sirven para
+//! cualquier tipo de celda. [`Grid::walkable`] usa la regla habitual del mapa
+//! de un nivel, y cada uso puede dar la suya con [`Grid::new`]: el validador,
+//! por ejemplo, deja pasar las puertas cuyas llaves ya alcanzó.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::f32::consts::SQRT_2;
+
+use crate::game::Cell;
+
+/// Decide si se puede pasar de una celda a otra vecina.
+type Passable<'a> = Box<dyn Fn((usize, usize), (usize, usize)) -> bool + 'a>;
+/// Da la celda a la que salta quien entra en otra, si salta a alguna.
+type Warp<'a> = Box<dyn Fn((usize, usize)) -> Option<(usize, usize)> + 'a>;
+
+/// Desplazamientos a las celdas vecinas en horizontal y en vertical.
+const ORTHOGONAL: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+/// Desplazamientos a las celdas vecinas en diagonal.
+const DIAGONAL: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// La estimación del coste restante con la que A* elige qué celda explorar.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Heuristic {
+    /// Suma de las distancias en cada eje. Es exacta en un mapa abierto sin
+    /// diagonales, pero con diagonales sobreestima y el camino puede no ser el
+    /// más corto.
+    Manhattan,
+    /// Distancia con pasos en diagonal de coste √2. Es la adecuada para una
+    /// cuadrícula con diagonales.
+    Octile,
+}
+
+impl Heuristic {
+    /// Estima el coste de ir de `from` a `to`.
+    pub fn estimate(self, from: (usize, usize), to: (usize, usize)) -> f32 {
+        let dx = from.0.abs_diff(to.0) as f32;
+        let dy = from.1.abs_diff(to.1) as f32;
+        match self {
+            Heuristic::Manhattan => dx + dy,
+            Heuristic::Octile => dx.max(dy) + (SQRT_2 - 1.0) * dx.min(dy),
+        }
+    }
+}
+
+/// Una cuadrícula de `width × height` celdas sobre la que se buscan caminos.
+///
+/// Un paso recto cuesta 1 y uno en diagonal √2. Los pasos en diagonal solo se
+/// dan si se activan con [`Grid::with_diagonals`] y si también se pueden dar
+/// los dos pasos rectos que lo forman, para no atravesar esquinas.
+pub struct Grid<'a> {
+    width: usize,
+    height: usize,
+    diagonals: bool,
+    passable: Passable<'a>,
+    warp: Option<Warp<'a>>,
+}
+
+impl<'a> Grid<'a> {
+    /// Crea una cuadrícula en la que `passable(from, to)` decide si se puede
+    /// pasar de la celda `from` a su vecina `to`.
+    pub fn new(
+        width: usize,
+        height: usize,
+        passable: impl Fn((usize, usize), (usize, usize)) -> bool + 'a,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            diagonals: false,
+            passable: Box::new(passable),
+            warp: None,
+        }
+    }
+
+    /// Crea la cuadrícula de un mapa con la regla de paso habitual: no se
+    /// entra en celdas sólidas ni se va en contra de una cinta.
+    pub fn walkable(map: &'a [Vec<Cell>]) -> Self {
+        let width = map.first().map_or(0, |row| row.len());
+        Self::new(width, map.len(), move |from, to| {
+            can_step(map, from, to, |cell| !cell.is_solid())
+        })
+    }
+
+    /// Devuelve la cuadrícula permitiendo también los pasos en diagonal.
+    pub fn with_diagonals(mut self) -> Self {
+        self.diagonals = true;
+        self
+    }
+
+    /// Devuelve la cuadrícula con saltos: quien entra en una celda para la que
+    /// `warp` devuelve otra aparece en esa otra, como con los teletransportadores.
+    /// Los saltos no cuestan nada y solo se siguen hacia delante, así que no
+    /// cuentan en los campos de flujo.
+    pub fn with_warps(
+        mut self,
+        warp: impl Fn((usize, usize)) -> Option<(usize, usize)> + 'a,
+    ) -> Self {
+        self.warp = Some(Box::new(warp));
+        self
+    }
+
+    /// Devuelve el ancho de la cuadrícula.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Devuelve el alto de la cuadrícula.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Devuelve las celdas a las que se llega con un paso desde `pos`, después
+    /// de seguir los saltos, junto con el coste del paso.
+    pub fn neighbors(
+        &self,
+        pos: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), f32)> + '_ {
+        self.offsets()
+            .filter_map(move |offset| self.step(pos, offset))
+            .map(move |(to, cost)| (self.warp_target(to), cost))
+    }
+
+    /// Búsqueda en anchura: devuelve cuántos pasos separan `start` de cada
+    /// celda, o `None` en las que no se alcanzan. Un paso en diagonal cuenta
+    /// como uno.
+    pub fn bfs(&self, start: (usize, usize)) -> Vec<Vec<Option<usize>>> {
+        let mut steps = vec![vec![None; self.width]; self.height];
+        let mut queue = VecDeque::new();
+        steps[start.1][start.0] = Some(0);
+        queue.push_back((start, 0));
+
+        while let Some((pos, count)) = queue.pop_front() {
+            for ((x, y), _) in self.neighbors(pos) {
+                if steps[y][x].is_none() {
+                    steps[y][x] = Some(count + 1);
+                    queue.push_back(((x, y), count + 1));
+                }
+            }
+        }
+        steps
+    }
+
+    /// Busca con A* el camino más barato de `start` a `goal`. Devuelve las
+    /// celdas del camino, sin incluir `start`, o `None` si no hay ninguno. Con
+    /// saltos, el camino puede no ser el más barato, porque la heurística no
+    /// los tiene en cuenta.
+    pub fn find_path(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+        heuristic: Heuristic,
+    ) -> Option<Vec<(usize, usize)>> {
+        let mut cost = vec![vec![f32::INFINITY; self.width]; self.height];
+        let mut came_from = vec![vec![None; self.width]; self.height];
+        let mut open = BinaryHeap::new();
+        cost[start.1][start.0] = 0.0;
+        open.push(Frontier::new(heuristic.estimate(start, goal), start));
+
+        while let Some(Frontier { pos, .. }) = open.pop() {
+            if pos == goal {
+                let mut path = Vec::new();
+                let mut current = goal;
+                while current != start {
+                    path.push(current);
+                    current = came_from[current.1][current.0]?;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let here = cost[pos.1][pos.0];
+            for ((x, y), step) in self.neighbors(pos) {
+                let next = here + step;
+                if next < cost[y][x] {
+                    cost[y][x] = next;
+                    came_from[y][x] = Some(pos);
+                    open.push(Frontier::new(
+                        next + heuristic.estimate((x, y), goal),
+                        (x, y),
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// Mapa de distancias (Dijkstra): devuelve el coste mínimo para llegar a
+    /// cada celda desde la más cercana de `sources`, o `None` en las que no se
+    /// alcanzan.
+    pub fn distance_map(&self, sources: &[(usize, usize)]) -> Vec<Vec<Option<f32>>> {
+        self.dijkstra(sources, false).0
+    }
+
+    /// Calcula el campo de flujo hacia `goals`: para cada celda, el paso con
+    /// el que se acerca más barato al objetivo más cercano. Sirve para mover a
+    /// muchas entidades hacia el mismo sitio con una sola búsqueda.
+    pub fn flow_field(&self, goals: &[(usize, usize)]) -> FlowField {
+        let (distances, next) = self.dijkstra(goals, true);
+        FlowField { distances, next }
+    }
+
+    /// Dijkstra desde varias celdas a la vez. Con `reverse`, recorre los pasos
+    /// al revés, de modo que las distancias son hasta `sources` en lugar de
+    /// desde ellas, y también devuelve el siguiente paso de cada celda hacia
+    /// ellas.
+    #[allow(clippy::type_complexity)]
+    fn dijkstra(
+        &self,
+        sources: &[(usize, usize)],
+        reverse: bool,
+    ) -> (Vec<Vec<Option<f32>>>, Vec<Vec<Option<(usize, usize)>>>) {
+        let mut cost = vec![vec![f32::INFINITY; self.width]; self.height];
+        let mut next = vec![vec![None; self.width]; self.height];
+        let mut open = BinaryHeap::new();
+        for &(x, y) in sources {
+            cost[y][x] = 0.0;
+            open.push(Frontier::new(0.0, (x, y)));
+        }
+
+        while let Some(Frontier { priority, pos }) = open.pop() {
+            if priority > cost[pos.1][pos.0] {
+                continue;
+            }
+            let steps: Vec<_> = if reverse {
+                self.predecessors(pos).collect()
+            } else {
+                self.neighbors(pos).collect()
+            };
+            for ((x, y), step) in steps {
+                let total = priority + step;
+                if total < cost[y][x] {
+                    cost[y][x] = total;
+                    if reverse {
+                        next[y][x] = Some(pos);
+                    }
+                    open.push(Frontier::new(total, (x, y)));
+                }
+            }
+        }
+
+        let distances = cost
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|cost| cost.is_finite().then_some(cost))
+                    .collect()
+            })
+            .collect();
+        (distances, next)
+    }
+
+    /// Devuelve los desplazamientos a las celdas vecinas: los rectos y, si
+    /// están activados, los diagonales.
+    fn offsets(&self) -> impl Iterator<Item = (isize, isize)> {
+        let diagonals = if self.diagonals { &DIAGONAL[..] } else { &[] };
+        ORTHOGONAL.into_iter().chain(diagonals.iter().copied())
+    }
+
+    /// Devuelve la celda vecina a `from` en la dirección `offset` y el coste
+    /// del paso, si se puede dar. No sigue los saltos.
+    fn step(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<((usize, usize), f32)> {
+        let to = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        if to.0 >= self.width || to.1 >= self.height || !(self.passable)((x, y), to) {
+            return None;
+        }
+        if dx != 0 && dy != 0 {
+            // En diagonal hay que poder pasar por los dos lados de la esquina.
+            self.step((x, y), (dx, 0))?;
+            self.step((x, y), (0, dy))?;
+            return Some((to, SQRT_2));
+        }
+        Some((to, 1.0))
+    }
+
+    /// Devuelve las celdas desde las que se llega a `pos` con un paso, sin
+    /// contar los saltos, junto con el coste del paso.
+    fn predecessors(
+        &self,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), f32)> + '_ {
+        self.offsets().filter_map(move |(dx, dy)| {
+            let from = (x.checked_add_signed(-dx)?, y.checked_add_signed(-dy)?);
+            if from.0 >= self.width || from.1 >= self.height {
+                return None;
+            }
+            let (to, cost) = self.step(from, (dx, dy))?;
+            (to == (x, y)).then_some((from, cost))
+        })
+    }
+
+    /// Devuelve la celda a la que se llega al entrar en `pos`.
+    fn warp_target(&self, pos: (usize, usize)) -> (usize, usize) {
+        self.warp.as_ref().and_then(|warp| warp(pos)).unwrap_or(pos)
+    }
+}
+
+/// El resultado de [`Grid::flow_field`]: el coste y el siguiente paso desde
+/// cada celda hacia el objetivo más cercano.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowField {
+    distances: Vec<Vec<Option<f32>>>,
+    next: Vec<Vec<Option<(usize, usize)>>>,
+}
+
+impl FlowField {
+    /// Devuelve la celda a la que hay que ir desde `pos` para acercarse al
+    /// objetivo más cercano, o `None` en un objetivo y en las celdas desde las
+    /// que no se llega a ninguno.
+    pub fn next(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        *self.next.get(y)?.get(x)?
+    }
+
+    /// Devuelve el coste de ir desde `pos` al objetivo más cercano, o `None` si
+    /// desde allí no se llega a ninguno.
+    pub fn distance(&self, (x, y): (usize, usize)) -> Option<f32> {
+        *self.distances.get(y)?.get(x)?
+    }
+}
+
+/// Indica si se puede pasar de una celda del mapa a otra vecina: la de destino
+/// debe dejar pasar según `passable` y el paso no puede ir en contra de la
+/// cinta de ninguna de las dos.
+pub fn can_step(
+    map: &[Vec<Cell>],
+    from: (usize, usize),
+    to: (usize, usize),
+    passable: impl Fn(Cell) -> bool,
+) -> bool {
+    let (dx, dy) = (to.0 as f32 - from.0 as f32, to.1 as f32 - from.1 as f32);
+    let against = |cell: Cell| matches!(cell, Cell::OneWay(heading) if heading.opposes(dx, dy));
+    let target = map[to.1][to.0];
+    passable(target) && !against(map[from.1][from.0]) && !against(target)
+}
+
+/// Una celda pendiente de explorar, ordenada para que el montículo saque
+/// primero la de menor prioridad.
+#[derive(Clone, Copy, PartialEq)]
+struct Frontier {
+    priority: f32,
+    pos: (usize, usize),
+}
+
+impl Frontier {
+    fn new(priority: f32, pos: (usize, usize)) -> Self {
+        Self { priority, pos }
+    }
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then_with(|| other.pos.cmp(&self.pos))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::game::Level;
+
+    /// Carga todos los niveles de `levels/`, sin el manifiesto del paquete.
+    fn shipped_levels() -> Vec<(PathBuf, Level)> {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("levels");
+        let mut paths: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .filter(|path| path.file_name().is_some_and(|name| name != "pack.txt"))
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty());
+        paths
+            .into_iter()
+            .map(|path| {
+                let level = Level::load(&path).unwrap();
+                (path, level)
+            })
+            .collect()
+    }
+
+    /// Devuelve las posiciones de las celdas que cumplen una condición.
+    fn find(level: &Level, wanted: impl Fn(Cell) -> bool) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        for (y, row) in level.map.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if wanted(cell) {
+                    found.push((x, y));
+                }
+            }
+        }
+        found
+    }
+
+    /// La cuadrícula del nivel con todas las puertas abiertas, como si ya se
+    /// tuvieran sus llaves.
+    fn open_doors(level: &Level) -> Grid<'_> {
+        Grid::new(level.get_width(), level.get_height(), |from, to| {
+            can_step(&level.map, from, to, |cell| {
+                matches!(cell, Cell::Door { .. }) || !cell.is_solid()
+            })
+        })
+    }
+
+    /// Suma el coste de los pasos de un camino que empieza en `start`.
+    fn path_cost(start: (usize, usize), path: &[(usize, usize)]) -> f32 {
+        let mut cost = 0.0;
+        let mut from = start;
+        for &to in path {
+            cost += if from.0 != to.0 && from.1 != to.1 {
+                SQRT_2
+            } else {
+                1.0
+            };
+            from = to;
+        }
+        cost
+    }
+
+    #[test]
+    fn bfs_reaches_every_item_and_goal() {
+        for (path, level) in shipped_levels() {
+            let start = find(&level, |cell| cell == Cell::Start)[0];
+            let grid = open_doors(&level).with_warps(|(x, y)| level.teleport_destination(x, y));
+            let steps = grid.bfs(start);
+            for (x, y) in find(&level, |cell| cell.is_collectible() || cell == Cell::Goal) {
+                assert!(
+                    steps[y][x].is_some(),
+                    "{}: ({}, {}) no se alcanza desde el inicio",
+                    path.display(),
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_star_matches_bfs_distance() {
+        for (path, level) in shipped_levels() {
+            let start = find(&level, |cell| cell == Cell::Start)[0];
+            let grid = open_doors(&level);
+            let steps = grid.bfs(start);
+            for (x, y) in find(&level, |cell| !cell.is_solid()) {
+                let found = grid.find_path(start, (x, y), Heuristic::Manhattan);
+                assert_eq!(
+                    found.map(|path| path.len()),
+                    steps[y][x],
+                    "{}: camino a ({}, {})",
+                    path.display(),
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn diagonal_paths_are_no_longer() {
+        for (path, level) in shipped_levels() {
+            let start = find(&level, |cell| cell == Cell::Start)[0];
+            let straight = open_doors(&level);
+            let diagonal = open_doors(&level).with_diagonals();
+            for goal in find(&level, |cell| !cell.is_solid()) {
+                let Some(orthogonal) = straight.find_path(start, goal, Heuristic::Manhattan) else {
+                    continue;
+                };
+                let octile = diagonal
+                    .find_path(start, goal, Heuristic::Octile)
+                    .unwrap_or_else(|| {
+                        panic!("{}: sin camino en diagonal a {:?}", path.display(), goal)
+                    });
+                assert!(
+                    path_cost(start, &octile) <= path_cost(start, &orthogonal) + 1e-4,
+                    "{}: el camino en diagonal a {:?} es más largo",
+                    path.display(),
+                    goal
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flow_field_descends_to_the_goal() {
+        for (path, level) in shipped_levels() {
+            let goals = find(&level, |cell| cell == Cell::Goal);
+            for grid in [open_doors(&level), open_doors(&level).with_diagonals()] {
+                let field = grid.flow_field(&goals);
+                for cell in find(&level, |cell| !cell.is_solid()) {
+                    // El coste del campo es el del mapa de distancias desde la celda.
+                    let distances = grid.distance_map(&[cell]);
+                    let expected = goals
+                        .iter()
+                        .filter_map(|&(x, y)| distances[y][x])
+                        .min_by(f32::total_cmp);
+                    match (field.distance(cell), expected) {
+                        (Some(actual), Some(expected)) => assert!(
+                            (actual - expected).abs() < 1e-4,
+                            "{}: coste {} en {:?}, se esperaba {}",
+                            path.display(),
+                            actual,
+                            cell,
+                            expected
+                        ),
+                        (actual, expected) => {
+                            assert_eq!(actual, expected, "{}: {:?}", path.display(), cell)
+                        }
+                    }
+
+                    match field.next(cell) {
+                        Some(next) => assert!(
+                            matches!(
+                                (field.distance(next), field.distance(cell)),
+                                (Some(after), Some(before)) if after < before
+                            ),
+                            "{}: el paso de {:?} a {:?} no se acerca a la meta",
+                            path.display(),
+                            cell,
+                            next
+                        ),
+                        None => assert!(
+                            goals.contains(&cell) || field.distance(cell).is_none(),
+                            "{}: {:?} no tiene siguiente paso",
+                            path.display(),
+                            cell
+                        ),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,43 @@
+use crate::game::{Cell, KeyColor};
 use crate::math::Vec2;
 
+/// Radio por defecto del jugador, en celdas.
+pub const DEFAULT_PLAYER_RADIUS: f32 = 0.2;
+
+/// Vidas con las que el jugador empieza cada nivel.
+pub const DEFAULT_PLAYER_HEALTH: u32 = 3;
+
+/// Pasos de la búsqueda binaria con la que el jugador se acerca a una pared.
+const COLLISION_ITERATIONS: u32 = 8;
+
+/// Longitud mínima, en celdas, de los tramos en que se divide un paso largo.
+const MIN_SUBSTEP: f32 = 0.05;
+
+/// Los objetos que lleva el jugador.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Inventory {
+    keys: Vec<KeyColor>,
+}
+
+impl Inventory {
+    /// Guarda una llave. Tener dos llaves del mismo color no cambia nada.
+    pub fn add_key(&mut self, color: KeyColor) {
+        if !self.keys.contains(&color) {
+            self.keys.push(color);
+        }
+    }
+
+    /// Indica si el jugador tiene la llave de un color.
+    pub fn has_key(&self, color: KeyColor) -> bool {
+        self.keys.contains(&color)
+    }
+
+    /// Devuelve las llaves, en el orden en que se recogieron.
+    pub fn keys(&self) -> &[KeyColor] {
+        &self.keys
+    }
+}
+
 /// Representa al jugador en el juego.
 pub struct Player {
     /// La posición actual del jugador en el mapa.
@@ -8,6 +46,13 @@ pub struct Player {
     pub direction: Vec2,
     /// El campo de visión (Field of View) del jugador, en radianes.
     pub fov: f32,
+    /// El radio del círculo con el que el jugador choca con las paredes. Debe
+    /// ser menor que 0.5 para que quepa por los pasillos de una celda.
+    pub radius: f32,
+    /// Las llaves y demás objetos que el jugador ha recogido en el nivel.
+    pub inventory: Inventory,
+    /// Las vidas que le quedan; un enemigo le quita una cada vez que lo toca.
+    pub health: u32,
     /// Un indicador para saber si el jugador se ha movido, útil para optimizar el renderizado.
     pub has_moved: bool,
 }
@@ -19,15 +64,18 @@ impl Player {
             position: Vec2::new(x, y),
             direction: Vec2::new(1.0, 0.0), // Dirección inicial hacia la derecha.
             fov: std::f32::consts::PI / 3.0, // Campo de visión de 60 grados.
+            radius: DEFAULT_PLAYER_RADIUS,
+            inventory: Inventory::default(),
+            health: DEFAULT_PLAYER_HEALTH,
             has_moved: false,
         }
     }
 
     /// Crea un jugador a partir de la posición inicial definida en el mapa.
-    pub fn from_map(map: &[Vec<u8>]) -> Self {
+    pub fn from_map(map: &[Vec<Cell>]) -> Self {
         for (y, row) in map.iter().enumerate() {
             for (x, &cell) in row.iter().enumerate() {
-                if cell == 2 { // El número 2 representa la posición inicial del jugador.
+                if cell == Cell::Start {
                     return Self::new(x as f32 + 0.5, y as f32 + 0.5);
                 }
             }
@@ -35,72 +83,140 @@ impl Player {
         Self::new(1.5, 1.5) // Posición de respaldo si no se encuentra en el mapa.
     }
 
+    /// Devuelve el jugador con otro radio de colisión.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
     /// Rota la dirección del jugador en un ángulo determinado.
     pub fn rotate(&mut self, angle: f32) {
         self.direction = self.direction.rotate(angle);
         self.has_moved = true;
     }
 
-    /// Mueve al jugador hacia adelante, evitando colisiones con las paredes.
-    pub fn move_forward(&mut self, distance: f32, map: &[Vec<u8>]) {
-        let new_pos = Vec2::new(
-            self.position.x + self.direction.x * distance,
-            self.position.y + self.direction.y * distance,
-        );
-        
-        if self.is_valid_position(new_pos, map) {
-            self.position = new_pos;
-            self.has_moved = true;
-        }
+    /// Mueve al jugador hacia adelante, deslizándose por las paredes con las que choque.
+    pub fn move_forward(&mut self, distance: f32, map: &[Vec<Cell>]) {
+        self.walk(distance, 0.0, map);
     }
 
-    /// Mueve al jugador hacia atrás, evitando colisiones con las paredes.
-    pub fn move_backward(&mut self, distance: f32, map: &[Vec<u8>]) {
-        let new_pos = Vec2::new(
-            self.position.x - self.direction.x * distance,
-            self.position.y - self.direction.y * distance,
-        );
-        
-        if self.is_valid_position(new_pos, map) {
-            self.position = new_pos;
-            self.has_moved = true;
-        }
+    /// Mueve al jugador hacia atrás, deslizándose por las paredes con las que choque.
+    pub fn move_backward(&mut self, distance: f32, map: &[Vec<Cell>]) {
+        self.walk(-distance, 0.0, map);
+    }
+
+    /// Mueve al jugador lateralmente (strafe), deslizándose por las paredes con las que choque.
+    pub fn strafe(&mut self, distance: f32, map: &[Vec<Cell>]) {
+        self.walk(0.0, distance, map);
     }
 
-    /// Mueve al jugador lateralmente (strafe), evitando colisiones.
-    pub fn strafe(&mut self, distance: f32, map: &[Vec<u8>]) {
+    /// Mueve al jugador `forward` unidades hacia delante y `sideways` hacia la
+    /// derecha a la vez. Permite caminar en diagonal.
+    pub fn walk(&mut self, forward: f32, sideways: f32, map: &[Vec<Cell>]) {
         let strafe_dir = self.direction.rotate(std::f32::consts::PI / 2.0);
-        let new_pos = Vec2::new(
-            self.position.x + strafe_dir.x * distance,
-            self.position.y + strafe_dir.y * distance,
+        self.move_by(
+            Vec2::new(
+                self.direction.x * forward + strafe_dir.x * sideways,
+                self.direction.y * forward + strafe_dir.y * sideways,
+            ),
+            map,
         );
+    }
 
-        if self.is_valid_position(new_pos, map) {
-            self.position = new_pos;
+    /// Desplaza al jugador evitando las paredes. Cada eje se resuelve por
+    /// separado, de modo que al chocar en diagonal contra una pared el jugador
+    /// se desliza a lo largo de ella en lugar de detenerse, y en cada eje se
+    /// acerca a la pared todo lo posible.
+    pub fn move_by(&mut self, delta: Vec2, map: &[Vec<Cell>]) {
+        let start = self.position;
+        let moved_x = self.advance(start, Vec2::new(delta.x, 0.0), map);
+        let moved = self.advance(moved_x, Vec2::new(0.0, delta.y), map);
+
+        if moved.x != start.x || moved.y != start.y {
+            self.position = moved;
             self.has_moved = true;
         }
     }
 
-    /// Comprueba si una posición es válida (no es una pared y está dentro de los límites del mapa).
-    fn is_valid_position(&self, pos: Vec2, map: &[Vec<u8>]) -> bool {
-        let x = pos.x as usize;
-        let y = pos.y as usize;
+    /// Devuelve la posición más lejana en el segmento de `from` a `from + step`
+    /// a la que el jugador puede llegar sin atravesar una pared ni ir contra
+    /// una cinta. Los pasos largos se recorren en tramos no mayores que el
+    /// radio, para no saltar por encima de una pared delgada.
+    fn advance(&self, from: Vec2, step: Vec2, map: &[Vec<Cell>]) -> Vec2 {
+        let pieces = (step.length() / self.radius.max(MIN_SUBSTEP)).ceil().max(1.0) as u32;
+        let piece = Vec2::new(step.x / pieces as f32, step.y / pieces as f32);
+
+        let mut position = from;
+        for _ in 0..pieces {
+            let target = Vec2::new(position.x + piece.x, position.y + piece.y);
+            let reached = self.advance_piece(position, piece, map);
+            position = reached;
+            if reached != target {
+                break;
+            }
+        }
+        position
+    }
 
-        if y >= map.len() || x >= map[0].len() {
-            return false;
+    /// Como `advance`, para un tramo que no puede saltar por encima de una pared.
+    fn advance_piece(&self, from: Vec2, step: Vec2, map: &[Vec<Cell>]) -> Vec2 {
+        let at = |t: f32| Vec2::new(from.x + step.x * t, from.y + step.y * t);
+        let allowed = |pos: Vec2| self.is_valid_position(pos, map) && !against_one_way(from, pos, step, map);
+        if allowed(at(1.0)) {
+            return at(1.0);
         }
 
-        let cell = map[y][x];
-        cell != 1 // El número 1 representa una pared.
+        // Búsqueda binaria de la fracción del paso que se puede recorrer.
+        let (mut reachable, mut blocked) = (0.0, 1.0);
+        for _ in 0..COLLISION_ITERATIONS {
+            let mid = (reachable + blocked) / 2.0;
+            if allowed(at(mid)) {
+                reachable = mid;
+            } else {
+                blocked = mid;
+            }
+        }
+        at(reachable)
+    }
+
+    /// Comprueba si el círculo del jugador, centrado en `pos`, cabe sin tocar
+    /// ninguna pared ni salir del mapa. Se comprueban todas las celdas que
+    /// cubre el círculo midiendo la distancia de su centro al punto más cercano
+    /// de cada celda sólida, por lo que las esquinas se tratan como redondeadas.
+    pub fn is_valid_position(&self, pos: Vec2, map: &[Vec<Cell>]) -> bool {
+        let height = map.len() as i32;
+        let width = map.first().map_or(0, |row| row.len()) as i32;
+
+        let min_x = (pos.x - self.radius).floor() as i32;
+        let max_x = (pos.x + self.radius).floor() as i32;
+        let min_y = (pos.y - self.radius).floor() as i32;
+        let max_y = (pos.y + self.radius).floor() as i32;
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let outside = x < 0 || y < 0 || x >= width || y >= height;
+                if !outside && !map[y as usize][x as usize].is_solid() {
+                    continue;
+                }
+
+                let nearest_x = pos.x.clamp(x as f32, x as f32 + 1.0);
+                let nearest_y = pos.y.clamp(y as f32, y as f32 + 1.0);
+                let (dx, dy) = (pos.x - nearest_x, pos.y - nearest_y);
+                if dx * dx + dy * dy < self.radius * self.radius {
+                    return false;
+                }
+            }
+        }
+        true
     }
 
     /// Comprueba si el jugador ha llegado a la celda objetivo.
-    pub fn is_at_goal(&self, map: &[Vec<u8>]) -> bool {
+    pub fn is_at_goal(&self, map: &[Vec<Cell>]) -> bool {
         let x = self.position.x as usize;
         let y = self.position.y as usize;
 
         if y < map.len() && x < map[0].len() {
-            map[y][x] == 3 // El número 3 representa la meta.
+            map[y][x] == Cell::Goal
         } else {
             false
         }
@@ -111,3 +227,100 @@ impl Player {
         (self.position.x as usize, self.position.y as usize)
     }
 }
+
+/// Indica si ir de `from` a `to` con un paso `step` va en contra de una cinta:
+/// recorriendo hacia atrás la cinta sobre la que está el jugador o entrando en
+/// una desde la celda a la que apunta.
+fn against_one_way(from: Vec2, to: Vec2, step: Vec2, map: &[Vec<Cell>]) -> bool {
+    let cell_at = |pos: Vec2| {
+        let (x, y) = (pos.x.floor(), pos.y.floor());
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        map.get(y).and_then(|row| row.get(x)).map(|&cell| ((x, y), cell))
+    };
+    let opposes = |cell: Option<((usize, usize), Cell)>| {
+        matches!(cell, Some((_, Cell::OneWay(heading))) if heading.opposes(step.x, step.y))
+    };
+
+    let (current, next) = (cell_at(from), cell_at(to));
+    opposes(current) || (next.map(|(pos, _)| pos) != current.map(|(pos, _)| pos) && opposes(next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Crea un mapa a partir de sus filas de glifos.
+    fn map(rows: &[&str]) -> Vec<Vec<Cell>> {
+        rows.iter()
+            .map(|row| row.chars().map(|glyph| Cell::from_glyph(glyph).unwrap()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn stays_a_radius_away_from_walls() {
+        let map = map(&["#####", "#...#", "#####"]);
+        let mut player = Player::new(1.5, 1.5);
+        for _ in 0..40 {
+            player.move_by(Vec2::new(0.1, 0.0), &map);
+        }
+        // La pared de la derecha empieza en x = 4.
+        assert!(player.position.x <= 4.0 - player.radius);
+        assert!(player.position.x > 4.0 - player.radius - 0.01);
+
+        player.move_by(Vec2::new(0.0, -1.0), &map);
+        assert!(player.position.y >= 1.0 + player.radius);
+        assert!(player.position.y < 1.0 + player.radius + 0.01);
+        assert!(player.is_valid_position(player.position, &map));
+    }
+
+    #[test]
+    fn a_long_step_does_not_jump_over_a_thin_wall() {
+        let map = map(&["#######", "#..#..#", "#######"]);
+        let mut player = Player::new(1.5, 1.5);
+        // Sin tramos, el paso terminaría en x = 4.0, libre, al otro lado de la pared.
+        player.move_by(Vec2::new(2.5, 0.0), &map);
+        assert!(player.position.x <= 3.0 - player.radius);
+        assert!(player.position.x > 3.0 - player.radius - 0.01);
+
+        let mut player = Player::new(1.5, 1.5);
+        player.move_by(Vec2::new(40.0, 0.0), &map);
+        assert!(player.position.x < 3.0);
+    }
+
+    #[test]
+    fn slides_along_a_wall_when_moving_diagonally() {
+        let map = map(&["#####", "#...#", "#...#", "#####"]);
+        let mut player = Player::new(2.0, 1.5);
+        player.move_by(Vec2::new(0.5, -0.5), &map);
+
+        // El eje x no choca y avanza entero; el eje y se detiene en la pared.
+        assert!((player.position.x - 2.5).abs() < 1e-5);
+        assert!(player.position.y >= 1.0 + player.radius);
+        assert!(player.position.y < 1.0 + player.radius + 0.01);
+        assert!(player.has_moved);
+    }
+
+    #[test]
+    fn does_not_clip_through_an_outside_corner() {
+        let map = map(&["#####", "#...#", "#.#.#", "#...#", "#####"]);
+        let player = Player::new(1.5, 1.5);
+
+        // La esquina del pilar en (2, 2) es redondeada: a 0.15 en cada eje el
+        // círculo aún no la toca, pero más cerca sí.
+        assert!(player.is_valid_position(Vec2::new(1.85, 1.85), &map));
+        assert!(!player.is_valid_position(Vec2::new(1.9, 1.9), &map));
+
+        // Avanzando en diagonal hacia la esquina, el jugador la rodea sin entrar en el pilar.
+        for start in [Vec2::new(1.5, 1.5), Vec2::new(1.7, 1.5), Vec2::new(1.5, 1.7)] {
+            let mut player = Player::new(start.x, start.y);
+            for _ in 0..40 {
+                player.move_by(Vec2::new(0.05, 0.05), &map);
+                assert!(player.is_valid_position(player.position, &map));
+                assert_ne!(player.get_grid_position(), (2, 2));
+            }
+        }
+    }
+}
@@ -0,0 +1,1183 @@
+//! El juego completo, independiente de la terminal.
+//!
+//! `Game` guarda el estado de la partida (pantalla actual, nivel, jugador,
+//! progreso) y lo hace avanzar a partir de tres llamadas: `handle_input` con
+//! cada acción del jugador (o `handle_key` con cada tecla, que la traduce con
+//! el `Keymap` de la configuración), `update` una vez por cuadro y `render`
+//! para dibujarlo en un `Backend`; además, `resize` con cada cambio de tamaño
+//! de la terminal. Quien lo usa solo decide de dónde salen las acciones y
+//! cuándo se llama a cada método.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crossterm::event::KeyCode;
+
+use crate::game::{
+    key_label, key_name, Action, Camera, CameraMode, Cell, Enemy, Entity, GameState, Keymap, Level, LevelLoadError, LevelPack, MazeAlgorithm, MazeConfig,
+    PackError, Player, DEFAULT_PLAYER_RADIUS,
+};
+use crate::graphics::{
+    Backend, BindingEntry, ColorMode, Framebuffer, GameRenderer, LevelSelectEntry, Screenshot, ScreenshotError,
+    ScreenshotFormat, TextureSet,
+};
+use crate::math::Vec2;
+
+/// Tamaño por defecto (en habitaciones) de los laberintos aleatorios.
+pub const DEFAULT_MAZE_SIZE: (usize, usize) = (8, 6);
+
+/// Velocidad por defecto del jugador, en celdas por segundo.
+pub const DEFAULT_MOVE_SPEED: f32 = 3.0;
+/// Velocidad máxima del jugador, en celdas por segundo. Más rápido, un cuadro
+/// lento lo haría avanzar varias celdas de golpe.
+pub const MAX_MOVE_SPEED: f32 = 10.0;
+/// Velocidad de giro por defecto del jugador, en radianes por segundo.
+pub const DEFAULT_TURN_SPEED: f32 = 1.6;
+
+/// Sin eventos de tecla soltada, cada pulsación (o repetición del sistema)
+/// mueve al jugador lo que recorrería en este tiempo, en segundos.
+const KEY_STEP_TIME: f32 = 0.05;
+/// Tiempo máximo, en segundos, que se avanza en un solo cuadro, para que una
+/// pausa larga no haga atravesar medio mapa de golpe.
+const MAX_FRAME_TIME: f32 = 0.1;
+/// Tiempo, en segundos, de cada paso de la animación de las puertas.
+const DOOR_STEP_TIME: f32 = 0.05;
+/// Distancia, en celdas desde su centro, a la que se abre una puerta.
+const DOOR_RANGE: f32 = 1.5;
+/// Velocidad, en celdas por segundo, a la que una cinta empuja al jugador.
+const CONVEYOR_SPEED: f32 = 2.0;
+/// Cada cuánto tiempo, en segundos, se recalculan los FPS.
+const FPS_INTERVAL: f32 = 0.5;
+
+/// Error producido al crear una partida.
+#[derive(Debug)]
+pub enum GameError {
+    /// El paquete de niveles no sirve para jugar, por ejemplo porque está vacío.
+    Pack(PackError),
+    /// Uno de los niveles del paquete no se puede cargar.
+    Load(LevelLoadError),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::Pack(err) => write!(f, "{}", err),
+            GameError::Load(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameError::Pack(err) => Some(err),
+            GameError::Load(err) => Some(err),
+        }
+    }
+}
+
+impl From<LevelLoadError> for GameError {
+    fn from(err: LevelLoadError) -> Self {
+        GameError::Load(err)
+    }
+}
+
+/// Opciones de una partida.
+pub struct GameConfig {
+    /// Semilla de los laberintos aleatorios; sin ella se usa la hora actual.
+    pub seed: Option<u64>,
+    /// Tamaño en habitaciones de los laberintos aleatorios.
+    pub maze_size: (usize, usize),
+    /// Algoritmo de generación de los laberintos aleatorios.
+    pub algorithm: Option<MazeAlgorithm>,
+    /// Colores que admite la terminal.
+    pub color_mode: ColorMode,
+    /// Muestra dos píxeles por carácter.
+    pub half_block: bool,
+    /// Dibuja la vista cenital con puntos Braille.
+    pub braille: bool,
+    /// Directorio en el que se guardan las capturas de pantalla.
+    pub screenshot_dir: PathBuf,
+    /// Velocidad del jugador, en celdas por segundo.
+    pub move_speed: f32,
+    /// Velocidad de giro del jugador, en radianes por segundo.
+    pub turn_speed: f32,
+    /// Radio del jugador al chocar con las paredes, en celdas (menor que 0.5).
+    pub player_radius: f32,
+    /// Teclas asignadas a cada acción.
+    pub keymap: Keymap,
+    /// Archivo en el que se guardan las teclas cambiadas en el juego.
+    pub keymap_path: PathBuf,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            maze_size: DEFAULT_MAZE_SIZE,
+            algorithm: None,
+            color_mode: ColorMode::detect(),
+            half_block: false,
+            braille: false,
+            screenshot_dir: PathBuf::from("screenshots"),
+            move_speed: DEFAULT_MOVE_SPEED,
+            turn_speed: DEFAULT_TURN_SPEED,
+            player_radius: DEFAULT_PLAYER_RADIUS,
+            keymap: Keymap::builtin(),
+            keymap_path: Keymap::default_path(),
+        }
+    }
+}
+
+/// Una partida: las pantallas del juego, el nivel en curso y el progreso en
+/// el paquete de niveles.
+pub struct Game {
+    config: GameConfig,
+    pack: LevelPack,
+    levels: Vec<Level>,
+    completed: Vec<bool>, // Niveles del paquete ya completados.
+    state: GameState,
+    running: bool,
+    level: Level,
+    current_level: usize,
+    collected_items: usize,
+    selected_level: usize, // Entrada elegida en la selección de nivel.
+    selected_binding: usize, // Acción elegida en la configuración de teclas.
+    capturing_key: bool, // La siguiente tecla se añade a la acción elegida.
+    bindings_changed: bool, // Hay teclas cambiadas sin guardar.
+    bindings_message: Option<String>, // Aviso al pie de la configuración de teclas.
+    save_warned: bool, // Ya se avisó de que no se pudieron guardar las teclas.
+    endless_seed: Option<u64>, // Semilla del laberinto actual en el modo infinito.
+    endless_depth: usize,
+    player: Player,
+    last_cell: (usize, usize), // Celda del jugador en el cuadro anterior, para detectar cuándo entra en otra.
+    enemies: Vec<Enemy>,
+    held: HashSet<Action>, // Acciones de movimiento con la tecla mantenida.
+    key_release_events: bool, // Se avisa de las teclas soltadas con `handle_key_release`.
+    camera: Camera,
+    renderer: GameRenderer,
+    fb_topdown: Framebuffer,
+    fb_firstperson: Framebuffer,
+    door_timer: f32, // Tiempo acumulado para el siguiente paso de las puertas.
+    redraw: bool, // Fuerza a dibujar el siguiente cuadro aunque el jugador no se haya movido.
+    notice: Option<String>, // Aviso que se muestra debajo de la interfaz.
+    fps: f32,
+    fps_frames: u32,
+    fps_elapsed: f32,
+}
+
+impl Game {
+    /// Crea una partida con los niveles del paquete, empezando en el menú
+    /// principal. Falla si algún nivel no se puede cargar o si el paquete no
+    /// tiene niveles.
+    pub fn new(pack: LevelPack, textures: TextureSet, config: GameConfig) -> Result<Self, GameError> {
+        let levels = pack.load_levels()?;
+        let level = levels
+            .first()
+            .cloned()
+            .ok_or_else(|| GameError::Pack(PackError::Empty(pack.dir.clone())))?;
+
+        // El tamaño real de la terminal se conoce al dibujar el primer cuadro.
+        let mut renderer = GameRenderer::new(80, 24, level.get_width(), level.get_height());
+        renderer.set_textures(textures);
+        renderer.set_color_mode(config.color_mode);
+        renderer.set_half_block(config.half_block);
+        renderer.set_braille(config.braille);
+        renderer.set_keymap(config.keymap.clone());
+
+        let player = Player::from_map(&level.map).with_radius(config.player_radius);
+
+        Ok(Self {
+            completed: vec![false; levels.len()],
+            last_cell: player.get_grid_position(),
+            player,
+            enemies: Vec::new(),
+            fb_topdown: renderer.top_down_framebuffer(),
+            fb_firstperson: renderer.first_person_framebuffer(),
+            config,
+            pack,
+            levels,
+            state: GameState::Menu,
+            running: true,
+            level,
+            current_level: 0,
+            collected_items: 0,
+            selected_level: 0,
+            selected_binding: 0,
+            capturing_key: false,
+            bindings_changed: false,
+            bindings_message: None,
+            save_warned: false,
+            endless_seed: None,
+            endless_depth: 0,
+            held: HashSet::new(),
+            key_release_events: false,
+            camera: Camera::new(),
+            renderer,
+            door_timer: 0.0,
+            redraw: true,
+            notice: None,
+            fps: 0.0,
+            fps_frames: 0,
+            fps_elapsed: 0.0,
+        })
+    }
+
+    /// Devuelve la pantalla actual.
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// Indica si el juego sigue en marcha; es `false` cuando el jugador sale.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Devuelve el nivel en curso.
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    /// Devuelve el jugador.
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    /// Devuelve los enemigos del nivel en curso.
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    /// Devuelve cuántos ítems se han recogido en el nivel en curso.
+    pub fn collected_items(&self) -> usize {
+        self.collected_items
+    }
+
+    /// Devuelve los cuadros por segundo medidos con `update`.
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    /// Devuelve las teclas asignadas a cada acción.
+    pub fn keymap(&self) -> &Keymap {
+        &self.config.keymap
+    }
+
+    /// Indica si quien usa el juego avisará de las teclas soltadas con
+    /// `handle_key_release`. En ese caso, las acciones de movimiento y giro duran
+    /// mientras la tecla esté pulsada y el jugador avanza en `update` según el
+    /// tiempo transcurrido. Si no, cada pulsación lo mueve un paso fijo.
+    pub fn set_key_release_events(&mut self, enabled: bool) {
+        self.key_release_events = enabled;
+        self.held.clear();
+    }
+
+    /// Aplica una tecla pulsada: la traduce a una acción con el `Keymap` de la
+    /// configuración. En la configuración de teclas, las flechas, ENTER,
+    /// RETROCESO y ESC funcionan siempre, para poder corregir cualquier
+    /// asignación.
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if self.state == GameState::Bindings {
+            self.bindings_key(key);
+            return;
+        }
+        match self.config.keymap.action(key) {
+            Some(action) => self.handle_input(action),
+            // En la pantalla de victoria cualquier tecla sale del juego.
+            None if self.state == GameState::Victory => self.running = false,
+            None => {}
+        }
+    }
+
+    /// Aplica una tecla soltada: termina la acción de movimiento que la tecla
+    /// mantenía, si había alguna.
+    pub fn handle_key_release(&mut self, key: KeyCode) {
+        if let Some(action) = self.config.keymap.action(key) {
+            self.handle_release(action);
+        }
+    }
+
+    /// Termina una acción de movimiento mantenida con `handle_input`.
+    pub fn handle_release(&mut self, action: Action) {
+        self.held.remove(&action);
+    }
+
+    /// Aplica una acción del jugador según la pantalla actual.
+    pub fn handle_input(&mut self, action: Action) {
+        match self.state {
+            GameState::Menu => match action {
+                Action::Confirm => self.state = GameState::LevelSelect,
+                Action::OpenBindings => {
+                    self.selected_binding = 0;
+                    self.capturing_key = false;
+                    self.bindings_message = None;
+                    self.state = GameState::Bindings;
+                }
+                Action::Back | Action::Quit => self.running = false,
+                _ => {}
+            },
+            GameState::LevelSelect => self.level_select_input(action),
+            GameState::Bindings => self.bindings_input(action),
+            GameState::Playing => self.playing_input(action),
+            // Cualquier acción cierra la pantalla de victoria.
+            GameState::Victory => self.running = false,
+        }
+    }
+
+    /// Avanza el juego `dt` segundos: mueve al jugador según las acciones
+    /// mantenidas y las cintas, lo teletransporta, recoge ítems y llaves, abre
+    /// y cierra las puertas, mueve a los enemigos y pasa de nivel cuando el
+    /// jugador llega a la meta. Debe llamarse una vez por cuadro.
+    pub fn update(&mut self, dt: f32) {
+        self.fps_frames += 1;
+        self.fps_elapsed += dt;
+        if self.fps_elapsed >= FPS_INTERVAL {
+            self.fps = self.fps_frames as f32 / self.fps_elapsed;
+            self.fps_frames = 0;
+            self.fps_elapsed = 0.0;
+        }
+
+        if self.state != GameState::Playing {
+            self.held.clear();
+            return;
+        }
+
+        // Con la terminal demasiado pequeña no se ve el nivel, así que nada se mueve.
+        let fits = self.renderer.fits_terminal();
+        if fits {
+            self.apply_held_movement(dt.min(MAX_FRAME_TIME));
+            self.apply_conveyor(dt.min(MAX_FRAME_TIME));
+        }
+        self.update_teleport();
+
+        let (px, py) = self.player.get_grid_position();
+        if self.level.collect_item(px, py) {
+            self.collected_items += 1;
+            self.redraw = true;
+        }
+        if let Some(color) = self.level.collect_key(px, py) {
+            self.player.inventory.add_key(color);
+            self.notice = Some(format!("Recogiste la llave {}", color.name()));
+            self.redraw = true;
+        }
+        self.update_doors(dt.min(MAX_FRAME_TIME));
+        if fits && self.update_enemies(dt.min(MAX_FRAME_TIME)) {
+            return;
+        }
+
+        if self.player.is_at_goal(&self.level.map) && self.collected_items >= self.level.required_items {
+            self.advance_level();
+        }
+    }
+
+    /// Dibuja la pantalla actual. Si el tamaño del backend cambió desde el
+    /// cuadro anterior, adapta antes la disposición.
+    pub fn render(&mut self, backend: &mut dyn Backend) -> io::Result<()> {
+        let (cols, rows) = backend.size();
+        if (cols as usize, rows as usize) != self.renderer.terminal_size() {
+            self.resize(cols, rows);
+        }
+
+        match self.state {
+            GameState::Menu => self.renderer.show_menu(backend),
+            GameState::LevelSelect => {
+                let entries = self.level_select_entries();
+                self.renderer.show_level_select(backend, self.selected_level, &entries)?;
+                match &self.notice {
+                    Some(notice) => self.renderer.display_notice(backend, notice),
+                    None => Ok(()),
+                }
+            }
+            GameState::Bindings => {
+                let entries = self.binding_entries();
+                self.renderer.show_bindings(
+                    backend,
+                    &entries,
+                    self.selected_binding,
+                    self.capturing_key,
+                    self.bindings_message.as_deref(),
+                )
+            }
+            GameState::Playing => self.render_playing(backend),
+            GameState::Victory => self.renderer.show_victory(backend),
+        }
+    }
+
+    /// Adapta la disposición a un nuevo tamaño de la terminal y fuerza a
+    /// dibujar de nuevo toda la pantalla en el siguiente cuadro. Quien recibe
+    /// los eventos de cambio de tamaño debe llamarlo con cada uno; `render`
+    /// solo lo detecta al dibujar, comparando con el tamaño del backend.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.renderer.resize(cols as usize, rows as usize);
+        self.renderer.invalidate();
+        self.fb_topdown = self.renderer.top_down_framebuffer();
+        self.fb_firstperson = self.renderer.first_person_framebuffer();
+        self.redraw = true;
+    }
+
+    fn render_playing(&mut self, backend: &mut dyn Backend) -> io::Result<()> {
+        if !self.player.has_moved && !self.redraw {
+            return Ok(());
+        }
+        self.player.has_moved = false;
+        self.redraw = false;
+
+        if !self.renderer.fits_terminal() {
+            return self.renderer.show_size_warning(backend);
+        }
+
+        let entities: Vec<Entity> = self.enemies.iter().map(|enemy| enemy.entity.clone()).collect();
+        match self.camera.mode {
+            CameraMode::TopDown => {
+                self.renderer.render_top_down(&mut self.fb_topdown, &self.level.map, &self.player, &entities);
+                self.renderer.display_framebuffer(backend, &self.fb_topdown)?;
+            }
+            CameraMode::FirstPerson => {
+                self.renderer.render_first_person(&mut self.fb_firstperson, &self.player, &self.level.map, &entities);
+                self.renderer.display_framebuffer(backend, &self.fb_firstperson)?;
+            }
+        }
+
+        let (level_number, total_levels) = match self.endless_seed {
+            Some(_) => (self.endless_depth, None),
+            None => (self.current_level + 1, Some(self.levels.len())),
+        };
+        self.renderer.display_ui(
+            backend,
+            self.fps,
+            &self.player,
+            self.collected_items,
+            self.level.required_items,
+            level_number,
+            total_levels,
+            &self.level.name,
+            !self.enemies.is_empty(),
+        )?;
+        if let Some(notice) = &self.notice {
+            self.renderer.display_notice(backend, notice)?;
+        }
+        Ok(())
+    }
+
+    /// Devuelve las entradas de la selección de nivel: los niveles del paquete
+    /// y, al final, el modo infinito.
+    fn level_select_entries(&self) -> Vec<LevelSelectEntry> {
+        level_select_entries(&self.pack, &self.levels, &self.completed, self.config.seed)
+    }
+
+    fn level_select_input(&mut self, action: Action) {
+        // La última entrada de la selección de nivel es el modo infinito.
+        let endless_entry = self.levels.len();
+        let last = endless_entry;
+        let page_size = self.renderer.level_select_page_size();
+        let page_start = self.selected_level / page_size * page_size;
+
+        match action {
+            Action::MoveForward => self.selected_level = self.selected_level.saturating_sub(1),
+            Action::MoveBackward => self.selected_level = (self.selected_level + 1).min(last),
+            Action::PageUp => self.selected_level = page_start.saturating_sub(page_size),
+            Action::PageDown => self.selected_level = (page_start + page_size).min(last),
+            Action::SelectSlot(slot) if slot < page_size && page_start + slot <= last => {
+                self.selected_level = page_start + slot;
+            }
+            Action::Confirm if self.selected_level == endless_entry => {
+                let seed = self.config.seed.unwrap_or_else(time_seed);
+                self.endless_seed = Some(seed);
+                self.endless_depth = 1;
+                let level = self.generate_maze(seed);
+                self.start_level(level);
+            }
+            Action::Confirm if self.pack.is_unlocked(self.selected_level, &self.completed) => {
+                // Reinicia el nivel elegido recargándolo desde disco. Si ya no se
+                // puede cargar, se avisa y se sigue en la selección de nivel.
+                match self.pack.load_level(self.selected_level) {
+                    Some(Ok(level)) => {
+                        self.levels[self.selected_level] = level.clone();
+                        self.endless_seed = None;
+                        self.current_level = self.selected_level;
+                        self.start_level(level);
+                    }
+                    Some(Err(err)) => self.notice = Some(format!("No se pudo cargar el nivel: {}", err)),
+                    None => {}
+                }
+            }
+            Action::Back => {
+                self.notice = None;
+                self.state = GameState::Menu;
+            }
+            Action::Quit => self.running = false,
+            _ => {}
+        }
+    }
+
+    /// Devuelve las entradas de la configuración de teclas, una por cada acción
+    /// asignable.
+    fn binding_entries(&self) -> Vec<BindingEntry> {
+        let keymap = &self.config.keymap;
+        Action::BINDABLE
+            .iter()
+            .map(|&action| BindingEntry {
+                label: action.label().to_string(),
+                keys: keymap.keys_label(action),
+                conflicts: keymap.conflicts(action).iter().map(|other| other.label().to_string()).collect(),
+            })
+            .collect()
+    }
+
+    fn bindings_key(&mut self, key: KeyCode) {
+        if self.capturing_key {
+            self.capturing_key = false;
+            if key == KeyCode::Esc {
+                return;
+            }
+            // Una tecla sin nombre en el archivo de teclas no se podría guardar
+            // ni volver a cargar.
+            if key_name(key).is_none() {
+                self.bindings_message = Some(format!("La tecla {} no se puede asignar", key_label(key)));
+                return;
+            }
+            self.config.keymap.bind(Action::BINDABLE[self.selected_binding], key);
+            self.bindings_changed = true;
+            return;
+        }
+
+        let action = match key {
+            KeyCode::Up => Action::MoveForward,
+            KeyCode::Down => Action::MoveBackward,
+            KeyCode::Enter => Action::Confirm,
+            KeyCode::Esc => Action::Back,
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.config.keymap.clear(Action::BINDABLE[self.selected_binding]);
+                self.bindings_changed = true;
+                return;
+            }
+            other => match self.config.keymap.action(other) {
+                Some(action) => action,
+                None => return,
+            },
+        };
+        self.bindings_input(action);
+    }
+
+    fn bindings_input(&mut self, action: Action) {
+        let last = Action::BINDABLE.len() - 1;
+        match action {
+            Action::MoveForward => self.selected_binding = self.selected_binding.saturating_sub(1),
+            Action::MoveBackward => self.selected_binding = (self.selected_binding + 1).min(last),
+            Action::PageUp => self.selected_binding = 0,
+            Action::PageDown => self.selected_binding = last,
+            Action::Confirm => {
+                self.capturing_key = true;
+                self.bindings_message = None;
+            }
+            Action::Back => self.leave_bindings(),
+            _ => {}
+        }
+    }
+
+    /// Sale de la configuración de teclas guardando los cambios. Si no se
+    /// pueden guardar, lo avisa y se queda; al volver a salir, las teclas
+    /// nuevas se usan solo en esta partida.
+    fn leave_bindings(&mut self) {
+        if self.bindings_changed {
+            if let Err(err) = self.config.keymap.save(&self.config.keymap_path) {
+                if !self.save_warned {
+                    self.save_warned = true;
+                    self.bindings_message = Some(format!(
+                        "No se pudieron guardar las teclas: {}. Vuelve a salir para usarlas solo en esta partida.",
+                        err
+                    ));
+                    return;
+                }
+            }
+            self.bindings_changed = false;
+        }
+        self.renderer.set_keymap(self.config.keymap.clone());
+        self.bindings_message = None;
+        self.save_warned = false;
+        self.state = GameState::Menu;
+    }
+
+    /// Empuja al jugador en el sentido de la cinta sobre la que está.
+    fn apply_conveyor(&mut self, dt: f32) {
+        let (px, py) = self.player.get_grid_position();
+        if let Some(&Cell::OneWay(heading)) = self.level.map.get(py).and_then(|row| row.get(px)) {
+            let push = heading.vector();
+            let distance = CONVEYOR_SPEED * dt;
+            self.player.move_by(Vec2::new(push.x * distance, push.y * distance), &self.level.map);
+        }
+    }
+
+    /// Lleva al jugador al otro extremo del teletransportador en el que acaba
+    /// de entrar. Al llegar, la celda de destino cuenta como ya pisada, para
+    /// no devolverlo al origen hasta que salga de ella y vuelva a entrar.
+    fn update_teleport(&mut self) {
+        let cell = self.player.get_grid_position();
+        if cell == self.last_cell {
+            return;
+        }
+        self.last_cell = cell;
+
+        if let Some((x, y)) = self.level.teleport_destination(cell.0, cell.1) {
+            self.player.position = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            self.player.has_moved = true;
+            self.last_cell = (x, y);
+            self.redraw = true;
+        }
+    }
+
+    /// Mueve a los enemigos. Si alguno toca al jugador, le quita una vida y lo
+    /// devuelve al punto de control, el inicio del nivel, con lo que ya había
+    /// recogido; sin vidas, el nivel vuelve a empezar. Devuelve `true` si el
+    /// jugador fue atrapado.
+    fn update_enemies(&mut self, dt: f32) -> bool {
+        if self.enemies.is_empty() {
+            return false;
+        }
+        for enemy in &mut self.enemies {
+            enemy.update(dt, self.player.position, &self.level.map);
+        }
+        self.redraw = true;
+
+        if !self.enemies.iter().any(|enemy| enemy.touches(&self.player)) {
+            return false;
+        }
+        self.player.health = self.player.health.saturating_sub(1);
+        if self.player.health == 0 {
+            self.restart_level();
+            self.notice = Some("Te quedaste sin vidas: el nivel vuelve a empezar".to_string());
+            return true;
+        }
+
+        let checkpoint = Player::from_map(&self.level.map);
+        self.player.position = checkpoint.position;
+        self.player.direction = checkpoint.direction;
+        self.player.has_moved = true;
+        self.last_cell = self.player.get_grid_position();
+        self.enemies = Self::spawn_enemies(&self.level);
+        self.notice = Some(format!("¡Te atrapó un enemigo! Vidas: {}", self.player.health));
+        true
+    }
+
+    /// Crea los enemigos de un nivel en sus posiciones iniciales.
+    fn spawn_enemies(level: &Level) -> Vec<Enemy> {
+        level
+            .enemies
+            .iter()
+            .enumerate()
+            .map(|(i, spawn)| Enemy::spawn(spawn, i as u64))
+            .collect()
+    }
+
+    /// Abre poco a poco las puertas cercanas cuya llave tiene el jugador y
+    /// cierra las demás, salvo si el jugador está dentro de una. Avisa cuando
+    /// el jugador se acerca a una puerta sin tener su llave.
+    fn update_doors(&mut self, dt: f32) {
+        self.door_timer += dt;
+        let steps = (self.door_timer / DOOR_STEP_TIME) as u32;
+        self.door_timer -= steps as f32 * DOOR_STEP_TIME;
+        // Ninguna puerta avanza más pasos de los que tiene su animación.
+        let steps = steps.min(u32::from(Cell::DOOR_STEPS)) as u8;
+
+        let position = self.player.position;
+        let radius = self.player.radius;
+        for y in 0..self.level.get_height() {
+            for x in 0..self.level.get_width() {
+                let Cell::Door { color, open } = self.level.map[y][x] else {
+                    continue;
+                };
+                let (dx, dy) = (x as f32 + 0.5 - position.x, y as f32 + 0.5 - position.y);
+                let near = dx * dx + dy * dy < DOOR_RANGE * DOOR_RANGE;
+                // El círculo del jugador toca la celda de la puerta.
+                let (nx, ny) = (position.x.clamp(x as f32, x as f32 + 1.0), position.y.clamp(y as f32, y as f32 + 1.0));
+                let inside = (position.x - nx).powi(2) + (position.y - ny).powi(2) < radius * radius;
+
+                let has_key = self.player.inventory.has_key(color);
+                if near && !has_key && open == 0 {
+                    let message = format!("Necesitas la llave {} para abrir esta puerta", color.name());
+                    if self.notice.as_ref() != Some(&message) {
+                        self.notice = Some(message);
+                        self.redraw = true;
+                    }
+                }
+
+                let open_next = if (near && has_key) || inside {
+                    open.saturating_add(steps).min(Cell::DOOR_STEPS)
+                } else {
+                    open.saturating_sub(steps)
+                };
+                if open_next != open {
+                    self.level.map[y][x] = Cell::Door { color, open: open_next };
+                    self.redraw = true;
+                }
+            }
+        }
+    }
+
+    /// Mueve y gira al jugador durante `dt` segundos según las acciones
+    /// mantenidas. Las acciones opuestas se anulan y en diagonal se avanza a la
+    /// misma velocidad que en línea recta.
+    fn apply_held_movement(&mut self, dt: f32) {
+        let axis = |positive: Action, negative: Action| {
+            self.held.contains(&positive) as i32 as f32 - self.held.contains(&negative) as i32 as f32
+        };
+        let turn = axis(Action::TurnRight, Action::TurnLeft);
+        let forward = axis(Action::MoveForward, Action::MoveBackward);
+        let sideways = axis(Action::StrafeRight, Action::StrafeLeft);
+
+        if turn != 0.0 {
+            self.player.rotate(turn * self.config.turn_speed * dt);
+        }
+        if forward != 0.0 || sideways != 0.0 {
+            let scale = self.config.move_speed * dt / (forward * forward + sideways * sideways).sqrt();
+            self.player.walk(forward * scale, sideways * scale, &self.level.map);
+        }
+    }
+
+    fn playing_input(&mut self, action: Action) {
+        let map = &self.level.map;
+        let step = self.config.move_speed * KEY_STEP_TIME;
+        let turn_step = self.config.turn_speed * KEY_STEP_TIME;
+        match action {
+            Action::Back | Action::Quit => self.running = false,
+            // Con la terminal demasiado pequeña solo se puede salir.
+            _ if !self.renderer.fits_terminal() => {}
+            Action::MoveForward
+            | Action::MoveBackward
+            | Action::StrafeLeft
+            | Action::StrafeRight
+            | Action::TurnLeft
+            | Action::TurnRight
+                if self.key_release_events =>
+            {
+                self.held.insert(action);
+            }
+            Action::MoveForward => self.player.move_forward(step, map),
+            Action::MoveBackward => self.player.move_backward(step, map),
+            Action::StrafeLeft => self.player.strafe(-step, map),
+            Action::StrafeRight => self.player.strafe(step, map),
+            Action::TurnLeft => self.player.rotate(-turn_step),
+            Action::TurnRight => self.player.rotate(turn_step),
+            Action::ToggleCamera => {
+                self.camera.toggle_mode();
+                self.redraw = true;
+            }
+            Action::ToggleHalfBlock | Action::ToggleBraille => {
+                if action == Action::ToggleHalfBlock {
+                    self.renderer.set_half_block(!self.renderer.is_half_block());
+                } else {
+                    self.renderer.set_braille(!self.renderer.is_braille());
+                }
+                self.fb_topdown = self.renderer.top_down_framebuffer();
+                self.fb_firstperson = self.renderer.first_person_framebuffer();
+                self.redraw = true;
+            }
+            Action::Screenshot => {
+                self.notice = Some(match self.save_screenshot() {
+                    Ok(base) => format!("Captura guardada en {}.{{ans,html,svg,ppm}}", base.display()),
+                    Err(err) => format!("No se pudo guardar la captura: {}", err),
+                });
+                self.redraw = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Pasa al siguiente nivel tras completar el actual: el siguiente laberinto
+    /// en el modo infinito, o el siguiente nivel desbloqueado del paquete. Si
+    /// no queda ninguno, muestra la pantalla de victoria.
+    fn advance_level(&mut self) {
+        if let Some(seed) = self.endless_seed {
+            // El siguiente laberinto usa la semilla siguiente.
+            let next_seed = seed.wrapping_add(1);
+            self.endless_seed = Some(next_seed);
+            self.endless_depth += 1;
+            let level = self.generate_maze(next_seed);
+            self.start_level(level);
+            return;
+        }
+
+        self.completed[self.current_level] = true;
+        let next_level = (self.current_level + 1..self.levels.len()).find(|&i| self.pack.is_unlocked(i, &self.completed));
+        match next_level {
+            Some(next_level) => {
+                self.current_level = next_level;
+                let level = self.levels[next_level].clone();
+                self.start_level(level);
+            }
+            None => self.state = GameState::Victory,
+        }
+    }
+
+    /// Prepara el jugador, el renderizador y el framebuffer cenital para jugar un nivel.
+    fn start_level(&mut self, level: Level) {
+        self.player = Player::from_map(&level.map).with_radius(self.config.player_radius);
+        self.last_cell = self.player.get_grid_position();
+        self.enemies = Self::spawn_enemies(&level);
+        self.held.clear();
+        self.renderer.set_map_size(level.get_width(), level.get_height());
+        self.fb_topdown = self.renderer.top_down_framebuffer();
+        self.level = level;
+        self.collected_items = 0;
+        self.notice = None;
+        self.redraw = true;
+        self.state = GameState::Playing;
+    }
+
+    /// Vuelve a empezar el nivel en curso desde su estado inicial.
+    fn restart_level(&mut self) {
+        let level = match self.endless_seed {
+            Some(seed) => self.generate_maze(seed),
+            None => self.levels[self.current_level].clone(),
+        };
+        self.start_level(level);
+    }
+
+    /// Genera el laberinto aleatorio correspondiente a una semilla.
+    fn generate_maze(&self, seed: u64) -> Level {
+        let (width, height) = self.config.maze_size;
+        let mut config = MazeConfig::new(seed, width, height);
+        config.algorithm = self.config.algorithm;
+        config.generate()
+    }
+
+    /// Guarda una captura de la vista actual en todos los formatos, con un
+    /// nombre basado en la hora actual. Devuelve la ruta de las capturas sin extensión.
+    fn save_screenshot(&self) -> Result<PathBuf, ScreenshotError> {
+        let dir = &self.config.screenshot_dir;
+        fs::create_dir_all(dir).map_err(|err| ScreenshotError::Io(dir.clone(), err))?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or(0);
+        let base = dir.join(format!("captura-{}", millis));
+
+        let framebuffer = match self.camera.mode {
+            CameraMode::TopDown => &self.fb_topdown,
+            CameraMode::FirstPerson => &self.fb_firstperson,
+        };
+        let screenshot = Screenshot::new(framebuffer).with_half_block(self.renderer.is_half_block());
+        for format in ScreenshotFormat::ALL {
+            screenshot.save(base.with_extension(format.extension()), format)?;
+        }
+        Ok(base)
+    }
+}
+
+/// Devuelve una semilla basada en la hora actual.
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Devuelve las entradas de la selección de nivel: los niveles del paquete,
+/// bloqueados o no según `completed`, y al final el modo infinito con su
+/// semilla, si la tiene.
+pub(crate) fn level_select_entries(
+    pack: &LevelPack,
+    levels: &[Level],
+    completed: &[bool],
+    seed: Option<u64>,
+) -> Vec<LevelSelectEntry> {
+    let mut entries: Vec<LevelSelectEntry> = levels
+        .iter()
+        .zip(&pack.entries)
+        .enumerate()
+        .map(|(i, (level, entry))| {
+            let unlocked = pack.is_unlocked(i, completed);
+            LevelSelectEntry {
+                name: level.name.clone(),
+                detail: if unlocked {
+                    format!("{} items requeridos", level.required_items)
+                } else {
+                    format!("bloqueado: {}", entry.unlock.describe())
+                },
+                unlocked,
+            }
+        })
+        .collect();
+    entries.push(LevelSelectEntry {
+        name: "Infinito / Aleatorio".to_string(),
+        detail: match seed {
+            Some(seed) => format!("laberintos generados, semilla {}", seed),
+            None => "laberintos generados sin fin".to_string(),
+        },
+        unlocked: true,
+    });
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::game::{PackEntry, UnlockRule, DEFAULT_PLAYER_HEALTH};
+    use crate::graphics::MemoryBackend;
+
+    /// Un pasillo con un ítem entre el inicio y la meta, que se gana andando
+    /// hacia delante.
+    const CORRIDOR: [&str; 3] = ["#######", "#S.*.G#", "#######"];
+
+    /// Crea un directorio temporal propio de la prueba.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("raytracer-maze-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn config(dir: &Path) -> GameConfig {
+        GameConfig {
+            seed: Some(1),
+            color_mode: ColorMode::Ansi16,
+            screenshot_dir: dir.join("screenshots"),
+            keymap_path: dir.join("keys.txt"),
+            ..GameConfig::default()
+        }
+    }
+
+    /// Crea una partida con un único nivel abierto, llamado `name` y con el
+    /// mapa `rows`, que pide todos los ítems del mapa. Devuelve también el
+    /// directorio temporal en el que se escribió, que la prueba debe borrar.
+    fn game_with_level(name: &str, rows: &[&str]) -> (Game, PathBuf) {
+        game_with_fields(name, &[], rows)
+    }
+
+    /// Como `game_with_level`, con campos de cabecera adicionales, como `enemy`.
+    fn game_with_fields(name: &str, fields: &[&str], rows: &[&str]) -> (Game, PathBuf) {
+        let dir = temp_dir(&name.to_lowercase());
+        let items = rows.iter().flat_map(|row| row.chars()).filter(|&glyph| glyph == '*').count();
+        let mut source = format!("name: {}\nrequired_items: {}\n", name, items);
+        for field in fields {
+            source.push_str(&format!("{}\n", field));
+        }
+        source.push_str(&format!("\n{}\n", rows.join("\n")));
+        let file = dir.join("nivel.txt");
+        fs::write(&file, source).unwrap();
+        let pack = LevelPack {
+            name: "Pruebas".to_string(),
+            dir: dir.clone(),
+            entries: vec![PackEntry {
+                file,
+                name: None,
+                unlock: UnlockRule::Open,
+            }],
+        };
+        let game = Game::new(pack, TextureSet::builtin(), config(&dir)).unwrap();
+        (game, dir)
+    }
+
+    #[test]
+    fn scripted_keys_play_a_level_to_victory() {
+        let (mut game, dir) = game_with_level("Victoria", &CORRIDOR);
+        let mut backend = MemoryBackend::new(100, 36);
+
+        game.render(&mut backend).unwrap();
+        assert_eq!(game.state(), GameState::Menu);
+        game.handle_key(KeyCode::Enter);
+        game.render(&mut backend).unwrap();
+        assert_eq!(game.state(), GameState::LevelSelect);
+        game.handle_key(KeyCode::Enter);
+        assert_eq!(game.state(), GameState::Playing);
+
+        for _ in 0..200 {
+            if game.state() != GameState::Playing {
+                break;
+            }
+            game.render(&mut backend).unwrap();
+            game.handle_key(KeyCode::Char('w'));
+            game.update(KEY_STEP_TIME);
+        }
+
+        assert_eq!(game.state(), GameState::Victory);
+        assert_eq!(game.collected_items(), 1);
+        game.render(&mut backend).unwrap();
+        assert!(backend.frame_count() > 3);
+        game.handle_key(KeyCode::Enter);
+        assert!(!game.is_running());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn resize_redraws_an_idle_level() {
+        let (mut game, dir) = game_with_level("Pasillo", &CORRIDOR);
+        game.handle_key(KeyCode::Enter);
+        game.handle_key(KeyCode::Enter);
+        game.render(&mut MemoryBackend::new(100, 36)).unwrap();
+
+        // Sin moverse, el siguiente cuadro no dibuja nada...
+        let mut idle = MemoryBackend::new(100, 36);
+        game.render(&mut idle).unwrap();
+        assert!(idle.text().trim().is_empty());
+
+        // ...salvo que llegue un cambio de tamaño, aunque sea al mismo.
+        game.resize(100, 36);
+        let mut redrawn = MemoryBackend::new(100, 36);
+        game.render(&mut redrawn).unwrap();
+        assert!(redrawn.text().contains("Pasillo"));
+
+        game.resize(30, 10);
+        let mut small = MemoryBackend::new(30, 10);
+        game.render(&mut small).unwrap();
+        assert!(small.text().contains("Terminal demasiado pequeña"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_long_stall_does_not_swing_doors_open() {
+        let (mut game, dir) = game_with_level("Puerta", &["#######", "#Sr.RG#", "#######"]);
+        game.handle_key(KeyCode::Enter);
+        game.handle_key(KeyCode::Enter);
+        game.render(&mut MemoryBackend::new(100, 36)).unwrap();
+
+        // Avanza con la llave hasta quedar junto a la puerta, sin esperar a que se abra.
+        while game.player().position.x < 3.2 {
+            game.handle_key(KeyCode::Char('w'));
+            game.update(0.0);
+        }
+        let door = |game: &Game| match game.level().map[1][4] {
+            Cell::Door { open, .. } => open,
+            other => panic!("se esperaba una puerta, se obtuvo {:?}", other),
+        };
+        let before = door(&game);
+
+        game.update(100.0);
+        let max_steps = (MAX_FRAME_TIME / DOOR_STEP_TIME).ceil() as u8;
+        let after = door(&game);
+        assert!(after > before);
+        assert!(after - before <= max_steps);
+        // La pausa no deja pasos pendientes para los cuadros siguientes.
+        game.update(0.0);
+        assert_eq!(door(&game), after);
+
+        // Con el tiempo la puerta termina de abrirse...
+        for _ in 0..Cell::DOOR_STEPS {
+            game.update(DOOR_STEP_TIME);
+        }
+        assert_eq!(door(&game), Cell::DOOR_STEPS);
+
+        // ...y se vuelve a cerrar cuando el jugador se aleja.
+        while game.player().position.x > 2.9 {
+            game.handle_key(KeyCode::Char('s'));
+            game.update(0.0);
+        }
+        for _ in 0..Cell::DOOR_STEPS {
+            game.update(DOOR_STEP_TIME);
+        }
+        assert_eq!(door(&game), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn an_enemy_costs_a_life_and_resets_the_level_when_none_are_left() {
+        let rows = ["########", "#S....G#", "########"];
+        let (mut game, dir) = game_with_fields("Guardia", &["enemy: patrol 5,1"], &rows);
+        game.handle_key(KeyCode::Enter);
+        game.handle_key(KeyCode::Enter);
+        let mut backend = MemoryBackend::new(100, 36);
+        game.render(&mut backend).unwrap();
+        assert!(backend.text().contains("Vidas: 3"));
+
+        // El jugador se queda quieto y el enemigo lo persigue hasta tocarlo.
+        let caught = |game: &mut Game| {
+            for _ in 0..100 {
+                let health = game.player().health;
+                game.update(0.1);
+                if game.player().health != health {
+                    return;
+                }
+            }
+            panic!("el enemigo no alcanza al jugador");
+        };
+        for lives in [2, 1] {
+            game.player.position.x = 2.5;
+            caught(&mut game);
+            assert_eq!(game.player().health, lives);
+            assert_eq!(game.player().position, Vec2::new(1.5, 1.5));
+            assert_eq!(game.enemies[0].position(), Vec2::new(5.5, 1.5));
+            assert_eq!(game.notice.as_deref(), Some(format!("¡Te atrapó un enemigo! Vidas: {}", lives).as_str()));
+        }
+
+        game.player.position.x = 2.5;
+        caught(&mut game);
+        assert_eq!(game.state(), GameState::Playing);
+        assert_eq!(game.player().health, DEFAULT_PLAYER_HEALTH);
+        assert_eq!(game.notice.as_deref(), Some("Te quedaste sin vidas: el nivel vuelve a empezar"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn lives_are_shown_only_with_enemies() {
+        let (mut game, dir) = game_with_level("Tranquilo", &CORRIDOR);
+        game.handle_key(KeyCode::Enter);
+        game.handle_key(KeyCode::Enter);
+        let mut backend = MemoryBackend::new(100, 36);
+        game.render(&mut backend).unwrap();
+        assert!(backend.text().contains("Tranquilo"));
+        assert!(!backend.text().contains("Vidas"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_level_that_no_longer_loads_shows_a_notice() {
+        let (mut game, dir) = game_with_level("Roto", &CORRIDOR);
+        fs::write(dir.join("nivel.txt"), "name: Roto\n\n#S?G#\n").unwrap();
+        game.handle_key(KeyCode::Enter);
+        game.handle_key(KeyCode::Enter);
+        assert_eq!(game.state(), GameState::LevelSelect);
+        assert!(game.is_running());
+
+        let mut backend = MemoryBackend::new(100, 36);
+        game.render(&mut backend).unwrap();
+        assert!(backend.text().contains("No se pudo cargar el nivel"));
+
+        fs::write(dir.join("nivel.txt"), format!("name: Roto\nrequired_items: 1\n\n{}\n", CORRIDOR.join("\n"))).unwrap();
+        game.handle_key(KeyCode::Enter);
+        assert_eq!(game.state(), GameState::Playing);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keys_without_a_name_are_not_bound() {
+        let (mut game, dir) = game_with_level("Teclas", &CORRIDOR);
+        let before = game.keymap().clone();
+
+        game.handle_key(KeyCode::Char('k'));
+        assert_eq!(game.state(), GameState::Bindings);
+        game.handle_key(KeyCode::Enter);
+        game.handle_key(KeyCode::F(13));
+        assert_eq!(game.keymap(), &before);
+        game.handle_key(KeyCode::Esc);
+        assert_eq!(game.state(), GameState::Menu);
+        assert!(!dir.join("keys.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn empty_pack_is_rejected() {
+        let dir = temp_dir("empty-pack");
+        let pack = LevelPack {
+            name: "Vacío".to_string(),
+            dir: dir.clone(),
+            entries: Vec::new(),
+        };
+        let result = Game::new(pack, TextureSet::builtin(), config(&dir));
+        assert!(matches!(result, Err(GameError::Pack(PackError::Empty(path))) if path == dir));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
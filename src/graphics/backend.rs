@@ -0,0 +1,210 @@
+//! Destinos de la salida del renderizador.
+//!
+//! `GameRenderer` no escribe directamente en la terminal sino en un `Backend`.
+//! `TerminalBackend` escribe en la terminal real y `MemoryBackend` guarda la
+//! pantalla en memoria como una cuadrícula de caracteres y colores, lo que
+//! permite renderizar sin una terminal (por ejemplo, en pruebas o en CI).
+
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Color, Print, PrintStyledContent, ResetColor, SetBackgroundColor, SetForegroundColor, StyledContent},
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::graphics::ScreenCell;
+
+/// Un destino en el que el renderizador dibuja la pantalla.
+pub trait Backend {
+    /// Devuelve el tamaño de la pantalla en caracteres (columnas, filas).
+    fn size(&self) -> (u16, u16);
+
+    /// Borra toda la pantalla.
+    fn clear(&mut self) -> io::Result<()>;
+
+    /// Escribe un texto con estilo a partir de la posición `(x, y)`.
+    fn print(&mut self, x: u16, y: u16, text: StyledContent<&str>) -> io::Result<()>;
+
+    /// Escribe celdas sueltas, cada una en su posición `(x, y)`.
+    fn draw_cells(&mut self, cells: &[(u16, u16, ScreenCell)]) -> io::Result<()>;
+
+    /// Muestra todo lo escrito desde la última llamada.
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// Escribe en la terminal real. La salida se acumula en memoria y se envía de
+/// una sola vez en cada `flush`.
+#[derive(Default)]
+pub struct TerminalBackend {
+    buffer: Vec<u8>,
+}
+
+impl TerminalBackend {
+    /// Crea un backend que escribe en la salida estándar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Backend for TerminalBackend {
+    fn size(&self) -> (u16, u16) {
+        terminal::size().unwrap_or((80, 24))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        queue!(self.buffer, Clear(ClearType::All))
+    }
+
+    fn print(&mut self, x: u16, y: u16, text: StyledContent<&str>) -> io::Result<()> {
+        queue!(self.buffer, MoveTo(x, y), PrintStyledContent(text))
+    }
+
+    fn draw_cells(&mut self, cells: &[(u16, u16, ScreenCell)]) -> io::Result<()> {
+        let mut cursor = None;
+        let mut foreground = None;
+        let mut background = None;
+
+        for &(x, y, cell) in cells {
+            // Las celdas contiguas del mismo color se escriben sin mover el
+            // cursor ni repetir la secuencia de color.
+            if cursor != Some((x, y)) {
+                queue!(self.buffer, MoveTo(x, y))?;
+            }
+            if foreground != Some(cell.foreground) {
+                queue!(self.buffer, SetForegroundColor(cell.foreground))?;
+                foreground = Some(cell.foreground);
+            }
+            if background != Some(cell.background) {
+                queue!(self.buffer, SetBackgroundColor(cell.background))?;
+                background = Some(cell.background);
+            }
+            queue!(self.buffer, Print(cell.symbol))?;
+            cursor = Some((x + 1, y));
+        }
+
+        if !cells.is_empty() {
+            queue!(self.buffer, ResetColor)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut stdout = io::stdout();
+        stdout.write_all(&self.buffer)?;
+        self.buffer.clear();
+        stdout.flush()
+    }
+}
+
+/// Guarda la pantalla en memoria como una cuadrícula de caracteres y colores.
+#[derive(Clone, Debug)]
+pub struct MemoryBackend {
+    width: u16,
+    height: u16,
+    cells: Vec<ScreenCell>,
+    flushes: usize,
+}
+
+impl MemoryBackend {
+    /// Crea una pantalla vacía de `width × height` caracteres.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![ScreenCell::BLANK; width as usize * height as usize],
+            flushes: 0,
+        }
+    }
+
+    /// Devuelve la celda en la posición `(x, y)`, si está dentro de la pantalla.
+    pub fn cell(&self, x: u16, y: u16) -> Option<ScreenCell> {
+        (x < self.width && y < self.height).then(|| self.cells[y as usize * self.width as usize + x as usize])
+    }
+
+    /// Devuelve el texto de la pantalla, una línea por fila y sin los espacios
+    /// finales de cada fila.
+    pub fn text(&self) -> String {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| {
+                let line: String = row.iter().map(|cell| cell.symbol).collect();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Devuelve el color de primer plano de cada celda, fila por fila.
+    pub fn foreground_colors(&self) -> Vec<Vec<Color>> {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| row.iter().map(|cell| cell.foreground).collect())
+            .collect()
+    }
+
+    /// Devuelve el color de fondo de cada celda, fila por fila.
+    pub fn background_colors(&self) -> Vec<Vec<Color>> {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| row.iter().map(|cell| cell.background).collect())
+            .collect()
+    }
+
+    /// Devuelve cuántos cuadros se han mostrado (llamadas a `flush`).
+    pub fn frame_count(&self) -> usize {
+        self.flushes
+    }
+
+    fn put(&mut self, x: u16, y: u16, cell: ScreenCell) {
+        if x < self.width && y < self.height {
+            self.cells[y as usize * self.width as usize + x as usize] = cell;
+        }
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.cells.fill(ScreenCell::BLANK);
+        Ok(())
+    }
+
+    fn print(&mut self, x: u16, y: u16, text: StyledContent<&str>) -> io::Result<()> {
+        let style = text.style();
+        let foreground = style.foreground_color.unwrap_or(Color::Reset);
+        let background = style.background_color.unwrap_or(Color::Reset);
+        for (i, symbol) in text.content().chars().enumerate() {
+            let Ok(offset) = u16::try_from(i) else { break };
+            self.put(
+                x.saturating_add(offset),
+                y,
+                ScreenCell {
+                    symbol,
+                    foreground,
+                    background,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn draw_cells(&mut self, cells: &[(u16, u16, ScreenCell)]) -> io::Result<()> {
+        for &(x, y, cell) in cells {
+            self.put(x, y, cell);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
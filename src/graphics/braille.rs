@@ -0,0 +1,190 @@
+//! Lienzo de puntos Braille para dibujar con más resolución que un carácter.
+//!
+//! Cada carácter Braille (U+2800 a U+28FF) tiene 2×4 puntos que se encienden de
+//! forma independiente, por lo que el lienzo tiene el doble de resolución
+//! horizontal y el cuádruple de resolución vertical que la terminal. Cada
+//! carácter tiene un único color: el del último punto dibujado en él.
+
+use crossterm::style::Color;
+
+use crate::graphics::{Framebuffer, Pixel};
+
+/// Primer carácter del bloque Braille, sin ningún punto encendido.
+const BRAILLE_BASE: u32 = 0x2800;
+
+/// Bit de cada punto dentro de un carácter, indexado por `[y][x]`.
+const DOT_BITS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Un lienzo de puntos Braille del tamaño de una región de caracteres.
+pub struct BrailleCanvas {
+    cols: usize,
+    rows: usize,
+    dots: Vec<u8>,
+    colors: Vec<Color>,
+}
+
+impl BrailleCanvas {
+    /// Crea un lienzo vacío de `cols × rows` caracteres.
+    pub fn new(cols: usize, rows: usize) -> Self {
+        Self {
+            cols,
+            rows,
+            dots: vec![0; cols * rows],
+            colors: vec![Color::Black; cols * rows],
+        }
+    }
+
+    /// Devuelve el ancho del lienzo en puntos.
+    pub fn width(&self) -> usize {
+        self.cols * 2
+    }
+
+    /// Devuelve la altura del lienzo en puntos.
+    pub fn height(&self) -> usize {
+        self.rows * 4
+    }
+
+    /// Enciende el punto `(x, y)` con un color. Los puntos fuera del lienzo se ignoran.
+    pub fn set(&mut self, x: isize, y: isize, color: Color) {
+        if x < 0 || y < 0 || x as usize >= self.width() || y as usize >= self.height() {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let index = (y / 4) * self.cols + x / 2;
+        self.dots[index] |= DOT_BITS[y % 4][x % 2];
+        self.colors[index] = color;
+    }
+
+    /// Dibuja una línea entre dos puntos, con coordenadas en puntos.
+    pub fn line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let x = x0 + (x1 - x0) * t;
+            let y = y0 + (y1 - y0) * t;
+            self.set(x.floor() as isize, y.floor() as isize, color);
+        }
+    }
+
+    /// Rellena un rectángulo, con coordenadas en puntos.
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        for dot_y in y.round() as isize..(y + height).round() as isize {
+            for dot_x in x.round() as isize..(x + width).round() as isize {
+                self.set(dot_x, dot_y, color);
+            }
+        }
+    }
+
+    /// Rellena una elipse inscrita en el rectángulo indicado, con coordenadas en
+    /// puntos. Como los puntos no son cuadrados, un círculo en pantalla necesita
+    /// radios distintos en cada eje.
+    pub fn fill_ellipse(&mut self, center_x: f32, center_y: f32, radius_x: f32, radius_y: f32, color: Color) {
+        let radius_x = radius_x.max(0.5);
+        let radius_y = radius_y.max(0.5);
+        for dot_y in (center_y - radius_y).floor() as isize..=(center_y + radius_y).ceil() as isize {
+            for dot_x in (center_x - radius_x).floor() as isize..=(center_x + radius_x).ceil() as isize {
+                let dx = (dot_x as f32 + 0.5 - center_x) / radius_x;
+                let dy = (dot_y as f32 + 0.5 - center_y) / radius_y;
+                if dx * dx + dy * dy <= 1.0 {
+                    self.set(dot_x, dot_y, color);
+                }
+            }
+        }
+    }
+
+    /// Copia el lienzo al framebuffer a partir de la posición `(x, y)` en
+    /// caracteres. Los caracteres sin puntos no modifican el framebuffer.
+    pub fn draw(&self, framebuffer: &mut Framebuffer, x: usize, y: usize) {
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let index = row * self.cols + col;
+                if self.dots[index] == 0 {
+                    continue;
+                }
+                let symbol = char::from_u32(BRAILLE_BASE + self.dots[index] as u32).unwrap_or(' ');
+                framebuffer.set_pixel(x + col, y + row, Pixel::new(self.colors[index], symbol));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dibuja el lienzo en un framebuffer de su mismo tamaño y devuelve sus filas de caracteres.
+    fn rows(canvas: &BrailleCanvas) -> Vec<String> {
+        let mut framebuffer = Framebuffer::new(canvas.cols, canvas.rows);
+        canvas.draw(&mut framebuffer, 0, 0);
+        framebuffer.pixels.iter().map(|row| row.iter().map(|pixel| pixel.symbol).collect()).collect()
+    }
+
+    #[test]
+    fn each_dot_sets_its_own_bit() {
+        let cases = [
+            ((0, 0), '⠁'),
+            ((0, 1), '⠂'),
+            ((0, 2), '⠄'),
+            ((0, 3), '⡀'),
+            ((1, 0), '⠈'),
+            ((1, 1), '⠐'),
+            ((1, 2), '⠠'),
+            ((1, 3), '⢀'),
+        ];
+        for ((x, y), expected) in cases {
+            let mut canvas = BrailleCanvas::new(1, 1);
+            canvas.set(x, y, Color::White);
+            assert_eq!(rows(&canvas), [expected.to_string()], "punto ({}, {})", x, y);
+        }
+
+        let mut canvas = BrailleCanvas::new(1, 1);
+        canvas.fill_rect(0.0, 0.0, 2.0, 4.0, Color::White);
+        assert_eq!(rows(&canvas), ["⣿"]);
+    }
+
+    #[test]
+    fn lines_cross_character_boundaries() {
+        let mut canvas = BrailleCanvas::new(2, 2);
+        canvas.line(0.0, 0.0, 3.0, 0.0, Color::White);
+        assert_eq!(rows(&canvas), ["⠉⠉", "  "]);
+
+        let mut canvas = BrailleCanvas::new(2, 2);
+        canvas.line(1.0, 0.0, 1.0, 7.0, Color::White);
+        assert_eq!(rows(&canvas), ["⢸ ", "⢸ "]);
+
+        let mut canvas = BrailleCanvas::new(2, 1);
+        canvas.line(0.0, 0.0, 3.0, 3.0, Color::White);
+        assert_eq!(rows(&canvas), ["⠑⢄"]);
+    }
+
+    #[test]
+    fn dots_outside_the_canvas_are_clipped() {
+        let mut canvas = BrailleCanvas::new(2, 1);
+        canvas.set(-1, 0, Color::White);
+        canvas.set(4, 0, Color::White);
+        canvas.set(0, 4, Color::White);
+        canvas.set(0, -1, Color::White);
+        assert_eq!(rows(&canvas), ["  "]);
+
+        canvas.line(-2.0, 1.0, 6.0, 1.0, Color::White);
+        assert_eq!(rows(&canvas), ["⠒⠒"]);
+    }
+
+    #[test]
+    fn draws_only_characters_with_dots_in_their_last_color() {
+        let mut canvas = BrailleCanvas::new(2, 1);
+        canvas.set(0, 0, Color::Red);
+        canvas.set(1, 3, Color::Green);
+
+        let mut framebuffer = Framebuffer::new(4, 2);
+        framebuffer.set_pixel(2, 0, Pixel::new(Color::Blue, '#'));
+        canvas.draw(&mut framebuffer, 1, 1);
+        canvas.draw(&mut framebuffer, 1, 0);
+
+        assert_eq!(framebuffer.get_pixel(1, 0), Some(&Pixel::new(Color::Green, '⢁')));
+        assert_eq!(framebuffer.get_pixel(1, 1), Some(&Pixel::new(Color::Green, '⢁')));
+        // El carácter vacío del lienzo deja lo que había debajo.
+        assert_eq!(framebuffer.get_pixel(2, 0), Some(&Pixel::new(Color::Blue, '#')));
+        assert_eq!(framebuffer.get_pixel(0, 0), Some(&Pixel::new(Color::Black, ' ')));
+    }
+}
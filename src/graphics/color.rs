@@ -0,0 +1,266 @@
+//! Colores RGB de 24 bits y su conversión a lo que admite la terminal.
+//!
+//! El renderizado en primera persona calcula los colores en RGB para atenuarlos
+//! de forma continua con la distancia. Al mostrarlos, `ColorMode` los convierte
+//! a color real, a la paleta de 256 colores o a los 16 colores con nombre,
+//! según lo que admita la terminal.
+
+use crossterm::style::Color;
+
+/// Un color RGB de 24 bits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// Los 16 colores con nombre y sus valores RGB habituales.
+const NAMED_COLORS: [(Color, Rgb); 16] = [
+    (Color::Black, Rgb::new(0, 0, 0)),
+    (Color::DarkRed, Rgb::new(128, 0, 0)),
+    (Color::DarkGreen, Rgb::new(0, 128, 0)),
+    (Color::DarkYellow, Rgb::new(128, 128, 0)),
+    (Color::DarkBlue, Rgb::new(0, 0, 128)),
+    (Color::DarkMagenta, Rgb::new(128, 0, 128)),
+    (Color::DarkCyan, Rgb::new(0, 128, 128)),
+    (Color::Grey, Rgb::new(192, 192, 192)),
+    (Color::DarkGrey, Rgb::new(128, 128, 128)),
+    (Color::Red, Rgb::new(255, 0, 0)),
+    (Color::Green, Rgb::new(0, 255, 0)),
+    (Color::Yellow, Rgb::new(255, 255, 0)),
+    (Color::Blue, Rgb::new(0, 0, 255)),
+    (Color::Magenta, Rgb::new(255, 0, 255)),
+    (Color::Cyan, Rgb::new(0, 255, 255)),
+    (Color::White, Rgb::new(255, 255, 255)),
+];
+
+/// Niveles de cada canal en el cubo de 6×6×6 colores de la paleta de 256.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Rgb {
+    /// Crea un color a partir de sus componentes.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Convierte un color de `crossterm` a RGB. Los colores con nombre usan sus
+    /// valores habituales; `Reset` se toma como negro.
+    pub fn from_color(color: Color) -> Self {
+        match color {
+            Color::Rgb { r, g, b } => Self::new(r, g, b),
+            Color::AnsiValue(index) => Self::from_ansi(index),
+            named => NAMED_COLORS
+                .iter()
+                .find(|(name, _)| *name == named)
+                .map_or(Self::default(), |&(_, rgb)| rgb),
+        }
+    }
+
+    /// Multiplica el brillo del color por `factor`.
+    pub fn scale(self, factor: f32) -> Self {
+        let channel = |c: u8| (c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+        Self::new(channel(self.r), channel(self.g), channel(self.b))
+    }
+
+    /// Mezcla el color con `other`: `t = 0` devuelve este color y `t = 1`, `other`.
+    pub fn lerp(self, other: Rgb, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Self::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+        )
+    }
+
+    /// Devuelve el índice de la paleta de 256 colores más parecido a este color,
+    /// eligiendo entre el cubo de colores y la rampa de grises.
+    pub fn to_ansi256(self) -> u8 {
+        let nearest_level = |c: u8| {
+            (0..CUBE_LEVELS.len())
+                .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+                .unwrap_or(0)
+        };
+        let (ri, gi, bi) = (nearest_level(self.r), nearest_level(self.g), nearest_level(self.b));
+        let cube = Rgb::new(CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+        let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+        // La rampa de grises va de 8 a 238 en pasos de 10 (índices 232 a 255).
+        let average = (self.r as u32 + self.g as u32 + self.b as u32) / 3;
+        let gray_step = (average.saturating_sub(3) / 10).min(23);
+        let gray_level = (8 + gray_step * 10) as u8;
+        let gray = Rgb::new(gray_level, gray_level, gray_level);
+
+        if self.distance_squared(gray) < self.distance_squared(cube) {
+            232 + gray_step as u8
+        } else {
+            cube_index as u8
+        }
+    }
+
+    /// Devuelve el color con nombre más parecido a este color.
+    pub fn to_named(self) -> Color {
+        NAMED_COLORS
+            .iter()
+            .min_by_key(|(_, rgb)| self.distance_squared(*rgb))
+            .map_or(Color::Black, |&(color, _)| color)
+    }
+
+    fn from_ansi(index: u8) -> Self {
+        match index {
+            0..=15 => NAMED_COLORS[index as usize].1,
+            16..=231 => {
+                let i = index - 16;
+                Self::new(
+                    CUBE_LEVELS[(i / 36) as usize],
+                    CUBE_LEVELS[(i / 6 % 6) as usize],
+                    CUBE_LEVELS[(i % 6) as usize],
+                )
+            }
+            _ => {
+                let level = 8 + (index - 232) * 10;
+                Self::new(level, level, level)
+            }
+        }
+    }
+
+    fn distance_squared(self, other: Rgb) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        d(self.r, other.r) + d(self.g, other.g) + d(self.b, other.b)
+    }
+}
+
+impl From<Rgb> for Color {
+    fn from(rgb: Rgb) -> Self {
+        Color::Rgb {
+            r: rgb.r,
+            g: rgb.g,
+            b: rgb.b,
+        }
+    }
+}
+
+/// Los colores que admite la terminal.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+    /// Color real de 24 bits.
+    TrueColor,
+    /// La paleta de 256 colores.
+    Ansi256,
+    /// Los 16 colores con nombre.
+    Ansi16,
+}
+
+impl ColorMode {
+    /// Detecta los colores que admite la terminal a partir de las variables de
+    /// entorno `COLORTERM` y `TERM`.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        Self::from_env(&colorterm, &term)
+    }
+
+    /// Devuelve el modo que corresponde a los valores de `COLORTERM` y `TERM`.
+    pub fn from_env(colorterm: &str, term: &str) -> Self {
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorMode::TrueColor
+        } else if term.contains("256color") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+
+    /// Devuelve el modo con el nombre indicado (`truecolor`, `256` o `16`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truecolor" | "24bit" => Some(ColorMode::TrueColor),
+            "256" => Some(ColorMode::Ansi256),
+            "16" => Some(ColorMode::Ansi16),
+            _ => None,
+        }
+    }
+
+    /// Convierte un color a uno que la terminal pueda mostrar. Los colores con
+    /// nombre se muestran siempre tal cual.
+    pub fn quantize(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorMode::TrueColor, _) => color,
+            (ColorMode::Ansi256, Color::Rgb { .. }) => {
+                Color::AnsiValue(Rgb::from_color(color).to_ansi256())
+            }
+            (ColorMode::Ansi16, Color::Rgb { .. } | Color::AnsiValue(_)) => {
+                Rgb::from_color(color).to_named()
+            }
+            _ => color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Rgb::new(r, g, b).into()
+    }
+
+    #[test]
+    fn true_color_keeps_every_color() {
+        for color in [rgb(12, 34, 56), Color::AnsiValue(200), Color::DarkCyan] {
+            assert_eq!(ColorMode::TrueColor.quantize(color), color);
+        }
+    }
+
+    #[test]
+    fn ansi256_rounds_each_channel_to_the_nearest_cube_level() {
+        let ansi256 = |color| ColorMode::Ansi256.quantize(color);
+        assert_eq!(ansi256(rgb(95, 135, 175)), Color::AnsiValue(16 + 36 + 2 * 6 + 3));
+        // 47 está más cerca de 0 y 48 más cerca de 95.
+        assert_eq!(ansi256(rgb(47, 255, 0)), Color::AnsiValue(16 + 5 * 6));
+        assert_eq!(ansi256(rgb(48, 255, 0)), Color::AnsiValue(16 + 36 + 5 * 6));
+        assert_eq!(ansi256(rgb(0, 0, 0)), Color::AnsiValue(16));
+        assert_eq!(ansi256(rgb(255, 255, 255)), Color::AnsiValue(231));
+    }
+
+    #[test]
+    fn ansi256_uses_the_gray_ramp_between_cube_levels() {
+        let ansi256 = |color| ColorMode::Ansi256.quantize(color);
+        assert_eq!(ansi256(rgb(8, 8, 8)), Color::AnsiValue(232));
+        assert_eq!(ansi256(rgb(128, 128, 128)), Color::AnsiValue(244));
+        assert_eq!(ansi256(rgb(238, 238, 238)), Color::AnsiValue(255));
+        // Un gris que está en el cubo se toma del cubo.
+        assert_eq!(ansi256(rgb(135, 135, 135)), Color::AnsiValue(16 + 2 * 36 + 2 * 6 + 2));
+    }
+
+    #[test]
+    fn ansi256_keeps_palette_and_named_colors() {
+        assert_eq!(ColorMode::Ansi256.quantize(Color::AnsiValue(100)), Color::AnsiValue(100));
+        assert_eq!(ColorMode::Ansi256.quantize(Color::Red), Color::Red);
+    }
+
+    #[test]
+    fn ansi16_picks_the_nearest_named_color() {
+        let ansi16 = |color| ColorMode::Ansi16.quantize(color);
+        // El punto medio entre DarkRed (128) y Red (255) es 191,5.
+        assert_eq!(ansi16(rgb(191, 0, 0)), Color::DarkRed);
+        assert_eq!(ansi16(rgb(192, 0, 0)), Color::Red);
+        // Y entre DarkGrey (128) y Grey (192), 160.
+        assert_eq!(ansi16(rgb(159, 159, 159)), Color::DarkGrey);
+        assert_eq!(ansi16(rgb(161, 161, 161)), Color::Grey);
+        assert_eq!(ansi16(Color::AnsiValue(196)), Color::Red);
+        assert_eq!(ansi16(Color::AnsiValue(3)), Color::DarkYellow);
+        assert_eq!(ansi16(Color::Magenta), Color::Magenta);
+    }
+
+    #[test]
+    fn detects_the_mode_from_the_environment() {
+        assert_eq!(ColorMode::from_env("truecolor", "xterm"), ColorMode::TrueColor);
+        assert_eq!(ColorMode::from_env("24bit", ""), ColorMode::TrueColor);
+        assert_eq!(ColorMode::from_env("", "xterm-256color"), ColorMode::Ansi256);
+        assert_eq!(ColorMode::from_env("", "xterm"), ColorMode::Ansi16);
+        assert_eq!(ColorMode::from_name("256"), Some(ColorMode::Ansi256));
+        assert_eq!(ColorMode::from_name("8"), None);
+    }
+}
@@ -1,11 +1,27 @@
 //! El módulo `graphics` contiene todas las estructuras y funciones relacionadas con el renderizado.
 
 // Declaración de los submódulos que componen el módulo de gráficos.
+pub mod backend;
+pub mod braille;
+pub mod color;
 pub mod framebuffer;
 pub mod pixel;
+pub mod presenter;
 pub mod renderer;
+pub mod screenshot;
+pub mod sprite;
+pub mod terminal;
+pub mod texture;
 
 // Exporta las estructuras y enums más importantes para que sean accesibles desde otros módulos.
+pub use backend::*;
+pub use braille::*;
+pub use color::*;
 pub use framebuffer::*;
 pub use pixel::*;
+pub use presenter::*;
 pub use renderer::*;
+pub use screenshot::*;
+pub use sprite::*;
+pub use terminal::*;
+pub use texture::*;
@@ -1,8 +1,10 @@
 use crossterm::style::Color;
 
+use crate::graphics::Rgb;
+
 /// Representa un único píxel en la terminal.
 /// Cada píxel tiene un color y un símbolo que lo representa.
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Pixel {
     /// El color del píxel.
     pub color: Color,
@@ -15,4 +17,24 @@ impl Pixel {
     pub fn new(color: Color, symbol: char) -> Self {
         Self { color, symbol }
     }
+
+    /// Crea un nuevo píxel con un color RGB de 24 bits. Al mostrarse, el color
+    /// se convierte a lo que admita la terminal.
+    pub fn rgb(color: Rgb, symbol: char) -> Self {
+        Self::new(color.into(), symbol)
+    }
+
+    /// Devuelve el color del píxel pintado entero, como en el modo de medio
+    /// bloque: los espacios son negros y los caracteres de sombreado (`░▒▓`)
+    /// aclaran el color según cuánto cubren.
+    pub fn fill_color(&self) -> Color {
+        let coverage = match self.symbol {
+            ' ' => return Color::Black,
+            '░' => 0.25,
+            '▒' => 0.5,
+            '▓' => 0.75,
+            _ => return self.color,
+        };
+        Rgb::from_color(self.color).scale(coverage).into()
+    }
 }
@@ -0,0 +1,211 @@
+//! Salida diferencial a la terminal.
+//!
+//! `Presenter` guarda dos búferes del tamaño de la región de juego: el cuadro
+//! que se está componiendo y el último que se mostró. Al presentar un cuadro
+//! solo se envían al `Backend` las celdas que cambiaron.
+
+use std::io;
+
+use crossterm::style::Color;
+
+use crate::graphics::Backend;
+
+/// Una celda de la terminal: un carácter con su color de primer plano y de fondo.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScreenCell {
+    pub symbol: char,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+impl ScreenCell {
+    /// Una celda vacía con los colores por defecto de la terminal.
+    pub const BLANK: ScreenCell = ScreenCell {
+        symbol: ' ',
+        foreground: Color::Reset,
+        background: Color::Reset,
+    };
+}
+
+/// Compone cuadros y los muestra escribiendo solo las diferencias con el anterior.
+pub struct Presenter {
+    width: usize,
+    height: usize,
+    /// El cuadro que se está componiendo.
+    back: Vec<ScreenCell>,
+    /// El último cuadro mostrado, o `None` si se desconoce lo que hay en pantalla.
+    front: Option<Vec<ScreenCell>>,
+    /// Celdas que cambiaron en el cuadro, reutilizadas entre cuadros.
+    changes: Vec<(u16, u16, ScreenCell)>,
+}
+
+impl Presenter {
+    /// Crea un presentador para una región de `width × height` celdas en la
+    /// esquina superior izquierda de la terminal.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            back: vec![ScreenCell::BLANK; width * height],
+            front: None,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Cambia el tamaño de la región. El siguiente cuadro se dibuja completo.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        *self = Self::new(width, height);
+    }
+
+    /// Olvida el último cuadro mostrado para que el siguiente se dibuje completo.
+    /// Debe llamarse cuando algo más escribe en la región (por ejemplo, al
+    /// borrar la pantalla para mostrar un menú).
+    pub fn invalidate(&mut self) {
+        self.front = None;
+    }
+
+    /// Vacía el cuadro que se está componiendo.
+    pub fn clear(&mut self) {
+        self.back.fill(ScreenCell::BLANK);
+    }
+
+    /// Escribe una celda del cuadro. Las posiciones fuera de la región se ignoran.
+    pub fn put(&mut self, x: usize, y: usize, cell: ScreenCell) {
+        if x < self.width && y < self.height {
+            self.back[y * self.width + x] = cell;
+        }
+    }
+
+    /// Muestra el cuadro compuesto enviando al backend solo las celdas que
+    /// cambiaron desde el último, y lo guarda como el cuadro mostrado. Si no se
+    /// conoce el último cuadro, borra la pantalla y envía el cuadro completo.
+    pub fn present(&mut self, backend: &mut dyn Backend) -> io::Result<()> {
+        self.changes.clear();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.width + x;
+                let cell = self.back[index];
+                if self.front.as_ref().is_none_or(|front| front[index] != cell) {
+                    self.changes.push((x as u16, y as u16, cell));
+                }
+            }
+        }
+
+        match &mut self.front {
+            Some(front) => front.copy_from_slice(&self.back),
+            None => {
+                backend.clear()?;
+                self.front = Some(self.back.clone());
+            }
+        }
+        backend.draw_cells(&self.changes)?;
+        backend.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::StyledContent;
+
+    use super::*;
+
+    /// Un backend que anota lo que recibe en cada cuadro.
+    #[derive(Default)]
+    struct Recorder {
+        clears: usize,
+        cells: Vec<(u16, u16, ScreenCell)>,
+    }
+
+    impl Backend for Recorder {
+        fn size(&self) -> (u16, u16) {
+            (4, 3)
+        }
+
+        fn clear(&mut self) -> io::Result<()> {
+            self.clears += 1;
+            Ok(())
+        }
+
+        fn print(&mut self, _x: u16, _y: u16, _text: StyledContent<&str>) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn draw_cells(&mut self, cells: &[(u16, u16, ScreenCell)]) -> io::Result<()> {
+            self.cells.extend_from_slice(cells);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn cell(symbol: char) -> ScreenCell {
+        ScreenCell {
+            symbol,
+            foreground: Color::White,
+            background: Color::Black,
+        }
+    }
+
+    /// Presenta un cuadro y devuelve las celdas enviadas al backend.
+    fn present(presenter: &mut Presenter, backend: &mut Recorder) -> Vec<(u16, u16, ScreenCell)> {
+        presenter.present(backend).unwrap();
+        std::mem::take(&mut backend.cells)
+    }
+
+    #[test]
+    fn the_first_frame_is_drawn_whole() {
+        let mut presenter = Presenter::new(4, 3);
+        let mut backend = Recorder::default();
+        presenter.put(1, 1, cell('#'));
+        let sent = present(&mut presenter, &mut backend);
+        assert_eq!(backend.clears, 1);
+        assert_eq!(sent.len(), 12);
+        assert!(sent.contains(&(1, 1, cell('#'))));
+    }
+
+    #[test]
+    fn later_frames_send_only_the_changed_cells() {
+        let mut presenter = Presenter::new(4, 3);
+        let mut backend = Recorder::default();
+        presenter.put(1, 1, cell('#'));
+        present(&mut presenter, &mut backend);
+
+        assert!(present(&mut presenter, &mut backend).is_empty());
+
+        presenter.put(3, 2, cell('@'));
+        presenter.put(1, 1, ScreenCell { foreground: Color::Red, ..cell('#') });
+        assert_eq!(
+            present(&mut presenter, &mut backend),
+            vec![(1, 1, ScreenCell { foreground: Color::Red, ..cell('#') }), (3, 2, cell('@'))]
+        );
+
+        presenter.clear();
+        assert_eq!(
+            present(&mut presenter, &mut backend),
+            vec![(1, 1, ScreenCell::BLANK), (3, 2, ScreenCell::BLANK)]
+        );
+        assert_eq!(backend.clears, 1);
+    }
+
+    #[test]
+    fn invalidate_and_resize_redraw_everything() {
+        let mut presenter = Presenter::new(4, 3);
+        let mut backend = Recorder::default();
+        present(&mut presenter, &mut backend);
+
+        presenter.invalidate();
+        assert_eq!(present(&mut presenter, &mut backend).len(), 12);
+        assert_eq!(backend.clears, 2);
+
+        presenter.resize(2, 2);
+        presenter.put(5, 5, cell('x'));
+        let blank = |x, y| (x, y, ScreenCell::BLANK);
+        assert_eq!(
+            present(&mut presenter, &mut backend),
+            vec![blank(0, 0), blank(1, 0), blank(0, 1), blank(1, 1)]
+        );
+        assert_eq!(backend.clears, 3);
+    }
+}
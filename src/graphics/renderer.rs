@@ -1,16 +1,36 @@
-use crossterm::{
-    ExecutableCommand,
-    cursor::MoveTo,
-    style::{Color, Stylize},
-    terminal::{Clear, ClearType, size},
-};
-use std::collections::{HashSet};
-use std::io::{Write, stdout};
-
-use crate::game::{Entity, Player, Level};
-use crate::graphics::{Framebuffer, Pixel};
+use crossterm::style::{Color, Stylize};
+use std::io;
+
+use crate::game::{key_label, Action, Cell, Entity, EntityType, Keymap, Player};
+use crate::graphics::{Backend, BrailleCanvas, ColorMode, Framebuffer, Pixel, Presenter, Rgb, ScreenCell, Sprite, TextureSet};
 use crate::math::Vec2;
 
+/// Ancho mínimo de la terminal para jugar, en caracteres.
+const MIN_TERMINAL_COLS: usize = 40;
+/// Altura mínima de la terminal para jugar, en caracteres, incluidas las cuatro
+/// filas de la interfaz.
+const MIN_TERMINAL_ROWS: usize = 12;
+
+/// Una entrada de la pantalla de selección de nivel.
+pub struct LevelSelectEntry {
+    /// El nombre que se muestra para la entrada.
+    pub name: String,
+    /// Información adicional, como los ítems requeridos o cómo desbloquearla.
+    pub detail: String,
+    /// Indica si la entrada puede jugarse.
+    pub unlocked: bool,
+}
+
+/// Una entrada de la pantalla de configuración de teclas.
+pub struct BindingEntry {
+    /// La descripción de la acción.
+    pub label: String,
+    /// Las teclas asignadas, tal como se muestran en pantalla.
+    pub keys: String,
+    /// Las otras acciones que comparten alguna tecla con esta.
+    pub conflicts: Vec<String>,
+}
+
 /// Gestiona todo el renderizado del juego, incluyendo el mapa, los menús y la interfaz de usuario.
 pub struct GameRenderer {
     cell_width: usize, // Ancho de una celda del mapa en caracteres.
@@ -18,7 +38,69 @@ pub struct GameRenderer {
     offset_x: u16, // Desplazamiento horizontal para centrar el mapa.
     offset_y: u16, // Desplazamiento vertical para centrar el mapa.
     minimap_size: usize, // Tamaño del minimapa en la vista de primera persona.
-    collected_positions: HashSet<(usize, usize)>, // Almacena las posiciones de los ítems recogidos.
+    depth_buffer: Vec<f32>, // Distancia a la pared más cercana en cada columna de la vista en primera persona.
+    textures: TextureSet, // Texturas de las paredes en la vista en primera persona.
+    color_mode: ColorMode, // Colores que admite la terminal.
+    half_block: bool, // Muestra dos píxeles por carácter con `▀`.
+    braille: bool, // Dibuja la vista cenital con puntos Braille.
+    terminal_cols: usize, // Ancho de la terminal en caracteres.
+    terminal_rows: usize, // Altura de la terminal en caracteres.
+    map_width: usize, // Ancho del mapa actual en celdas.
+    map_height: usize, // Altura del mapa actual en celdas.
+    presenter: Presenter, // Muestra solo lo que cambió desde el cuadro anterior.
+    keymap: Keymap, // Teclas que se muestran en los menús y en la interfaz.
+}
+
+/// La cara de una celda contra la que choca un rayo, nombrada según el punto
+/// cardinal hacia el que mira (el norte es `-y` en el mapa).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WallFace {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl WallFace {
+    /// Devuelve el vector normal de la cara, que apunta hacia fuera de la celda.
+    pub fn normal(self) -> Vec2 {
+        match self {
+            WallFace::North => Vec2::new(0.0, -1.0),
+            WallFace::South => Vec2::new(0.0, 1.0),
+            WallFace::East => Vec2::new(1.0, 0.0),
+            WallFace::West => Vec2::new(-1.0, 0.0),
+        }
+    }
+
+    /// Indica si la cara es perpendicular al eje `x` (caras este y oeste). El
+    /// rayo la alcanzó al cruzar una línea vertical de la cuadrícula.
+    pub fn is_x_side(self) -> bool {
+        matches!(self, WallFace::East | WallFace::West)
+    }
+
+    /// Iluminación relativa de la cara, para que las esquinas se distingan: las
+    /// caras norte y sur se dibujan más oscuras que las caras este y oeste.
+    pub fn light(self) -> f32 {
+        if self.is_x_side() { 1.0 } else { 0.7 }
+    }
+}
+
+/// El resultado de lanzar un rayo: dónde y contra qué pared chocó.
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    /// Distancia perpendicular desde el origen del rayo hasta la pared.
+    pub distance: f32,
+    /// El tipo de celda golpeada.
+    pub cell: Cell,
+    /// Posición exacta del impacto a lo largo de la cara de la pared, en `[0, 1)`.
+    /// Crece de izquierda a derecha vista desde el jugador.
+    pub wall_x: f32,
+    /// Columna del mapa de la celda golpeada.
+    pub map_x: i32,
+    /// Fila del mapa de la celda golpeada.
+    pub map_y: i32,
+    /// La cara de la celda golpeada.
+    pub face: WallFace,
 }
 
 impl GameRenderer {
@@ -29,12 +111,132 @@ impl GameRenderer {
         map_width: usize,
         map_height: usize,
     ) -> Self {
-        let rows = terminal_rows.saturating_sub(4);
-        let aspect_fix = 2.0; // Factor de corrección para el aspect ratio de los caracteres.
+        let mut renderer = Self {
+            cell_width: 1,
+            cell_height: 1,
+            offset_x: 0,
+            offset_y: 0,
+            minimap_size: minimap_size_for(terminal_cols, terminal_rows),
+            depth_buffer: Vec::new(),
+            textures: TextureSet::default(),
+            color_mode: ColorMode::detect(),
+            half_block: false,
+            braille: false,
+            terminal_cols,
+            terminal_rows,
+            map_width,
+            map_height,
+            presenter: Presenter::new(terminal_cols, terminal_rows.saturating_sub(4)),
+            keymap: Keymap::builtin(),
+        };
+        renderer.update_layout();
+        renderer
+    }
+
+    /// Prepara el renderizador para un nuevo nivel: recalcula el tamaño de las
+    /// celdas del mapa.
+    pub fn set_map_size(&mut self, map_width: usize, map_height: usize) {
+        self.map_width = map_width;
+        self.map_height = map_height;
+        self.update_layout();
+    }
+
+    /// Adapta el renderizador a un nuevo tamaño de la terminal: recalcula el
+    /// tamaño de las celdas del mapa, sus desplazamientos y el minimapa. Los
+    /// framebuffers deben volver a crearse y el siguiente cuadro se dibuja completo.
+    pub fn resize(&mut self, terminal_cols: usize, terminal_rows: usize) {
+        self.terminal_cols = terminal_cols;
+        self.terminal_rows = terminal_rows;
+        self.minimap_size = minimap_size_for(terminal_cols, terminal_rows);
+        self.presenter.resize(terminal_cols, terminal_rows.saturating_sub(4));
+        self.update_layout();
+    }
+
+    /// Devuelve el tamaño de la terminal (columnas, filas) para el que está
+    /// calculada la disposición actual.
+    pub fn terminal_size(&self) -> (usize, usize) {
+        (self.terminal_cols, self.terminal_rows)
+    }
+
+    /// Devuelve el tamaño mínimo de la terminal (columnas, filas) en el que cabe
+    /// el mapa actual con al menos un carácter por celda, además de la interfaz.
+    pub fn minimum_terminal_size(&self) -> (usize, usize) {
+        let map_rows = self.map_height.div_ceil(self.pixels_per_row());
+        (
+            self.map_width.max(MIN_TERMINAL_COLS),
+            (map_rows + 4).max(MIN_TERMINAL_ROWS),
+        )
+    }
+
+    /// Indica si la terminal tiene al menos el tamaño mínimo para el mapa actual.
+    pub fn fits_terminal(&self) -> bool {
+        let (min_cols, min_rows) = self.minimum_terminal_size();
+        self.terminal_cols >= min_cols && self.terminal_rows >= min_rows
+    }
+
+    /// Activa o desactiva el modo de medio bloque, en el que cada carácter de la
+    /// terminal muestra dos píxeles del framebuffer (`▀` con colores de primer
+    /// plano y de fondo distintos), duplicando la resolución vertical. Los
+    /// framebuffers deben volver a crearse después de cambiar el modo. Desactiva
+    /// el modo Braille.
+    pub fn set_half_block(&mut self, enabled: bool) {
+        self.half_block = enabled;
+        self.braille &= !enabled;
+        self.update_layout();
+    }
+
+    /// Activa o desactiva el modo Braille, en el que la vista cenital, la flecha
+    /// del jugador y los rayos de visión se dibujan con 2×4 puntos por carácter.
+    /// Desactiva el modo de medio bloque, por lo que los framebuffers deben
+    /// volver a crearse después de cambiar el modo.
+    pub fn set_braille(&mut self, enabled: bool) {
+        self.braille = enabled;
+        self.half_block &= !enabled;
+        self.update_layout();
+    }
+
+    /// Cambia las teclas que se muestran en los menús y en la interfaz.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    /// Indica si el modo Braille está activo.
+    pub fn is_braille(&self) -> bool {
+        self.braille
+    }
+
+    /// Indica si el modo de medio bloque está activo.
+    pub fn is_half_block(&self) -> bool {
+        self.half_block
+    }
+
+    /// Crea un framebuffer del tamaño de la vista cenital del mapa actual.
+    pub fn top_down_framebuffer(&self) -> Framebuffer {
+        Framebuffer::new(self.map_width * self.cell_width, self.map_height * self.cell_height)
+    }
+
+    /// Crea un framebuffer del tamaño de la vista en primera persona.
+    pub fn first_person_framebuffer(&self) -> Framebuffer {
+        Framebuffer::new(self.terminal_cols, self.terminal_rows.saturating_sub(4) * self.pixels_per_row())
+    }
+
+    /// Cantidad de filas del framebuffer que ocupa cada fila de la terminal.
+    fn pixels_per_row(&self) -> usize {
+        if self.half_block { 2 } else { 1 }
+    }
+
+    /// Calcula el tamaño de celda del mapa que mejor se ajusta a la terminal y
+    /// los desplazamientos para centrarlo.
+    fn update_layout(&mut self) {
+        let pixels_per_row = self.pixels_per_row();
+        let rows = self.terminal_rows.saturating_sub(4) * pixels_per_row;
+        // Factor de corrección para el aspect ratio de los caracteres; en modo de
+        // medio bloque cada píxel es aproximadamente cuadrado.
+        let aspect_fix = 2.0 / pixels_per_row as f32;
 
         // Calcula el tamaño de celda que mejor se ajusta a la terminal.
-        let max_cell_width_by_cols = terminal_cols / map_width;
-        let max_cell_height_by_rows = rows / map_height;
+        let max_cell_width_by_cols = self.terminal_cols / self.map_width;
+        let max_cell_height_by_rows = rows / self.map_height;
 
         let (cell_width, cell_height) = if max_cell_width_by_cols as f32 / aspect_fix <= max_cell_height_by_rows as f32 {
             let cw = max_cell_width_by_cols.max(1);
@@ -46,154 +248,167 @@ impl GameRenderer {
             (cw, ch)
         };
 
-        let map_pixel_width = map_width * cell_width;
-        let map_pixel_height = map_height * cell_height;
+        let map_pixel_width = self.map_width * cell_width;
+        let map_pixel_height = self.map_height * cell_height;
 
-        // Calcula los desplazamientos para centrar el mapa.
-        let offset_x = ((terminal_cols as isize - map_pixel_width as isize) / 2).max(0) as u16;
-        let offset_y = ((rows as isize - map_pixel_height as isize) / 2).max(0) as u16;
+        // Calcula los desplazamientos para centrar el mapa, en caracteres de la terminal.
+        self.offset_x = ((self.terminal_cols as isize - map_pixel_width as isize) / 2).max(0) as u16;
+        self.offset_y = ((rows as isize - map_pixel_height as isize) / 2 / pixels_per_row as isize).max(0) as u16;
+        self.cell_width = cell_width;
+        self.cell_height = cell_height;
+        self.presenter.invalidate();
+    }
 
-        let minimap_size = 14.min(terminal_cols / 6).min(terminal_rows / 6).max(10);
+    /// Hace que el siguiente cuadro se dibuje completo. Debe llamarse si algo
+    /// más escribió sobre la región de juego de la terminal.
+    pub fn invalidate(&mut self) {
+        self.presenter.invalidate();
+    }
 
-        Self {
-            cell_width,
-            cell_height,
-            offset_x,
-            offset_y,
-            minimap_size,
-            collected_positions: HashSet::new(),
-        }
+    /// Establece las texturas con las que se dibujan las paredes. Las paredes sin
+    /// textura se dibujan con su color plano.
+    pub fn set_textures(&mut self, textures: TextureSet) {
+        self.textures = textures;
+    }
+
+    /// Establece los colores que admite la terminal. Los colores RGB se
+    /// convierten al mostrarse si la terminal no admite color real.
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) {
+        self.color_mode = color_mode;
     }
 
     /// Muestra el menú principal del juego.
-    pub fn show_menu(&self) {
-        let mut stdout = stdout();
-        stdout.execute(Clear(ClearType::All)).unwrap();
+    pub fn show_menu(&mut self, backend: &mut dyn Backend) -> io::Result<()> {
+        backend.clear()?;
+        self.presenter.invalidate();
+
+        let keymap = &self.keymap;
+        let quit_keys = keymap.keys(Action::Quit).iter().chain(keymap.keys(Action::Back));
+        let controls = [
+            (
+                keymap.columns_label(&[Action::MoveForward, Action::StrafeLeft, Action::MoveBackward, Action::StrafeRight]),
+                "Mover jugador",
+            ),
+            (
+                format!("{} / {}", keymap.keys_label(Action::TurnLeft), keymap.keys_label(Action::TurnRight)),
+                "Rotar cámara",
+            ),
+            (keymap.keys_label(Action::ToggleCamera), "Cambiar vista"),
+            (keymap.keys_label(Action::ToggleHalfBlock), "Medio bloque"),
+            (keymap.keys_label(Action::ToggleBraille), "Mapa en Braille"),
+            (keymap.keys_label(Action::Screenshot), "Captura de pantalla"),
+            (keymap.keys_label(Action::OpenBindings), "Configurar teclas"),
+            (quit_keys.map(|&key| key_label(key)).collect::<Vec<_>>().join(" / "), "Salir"),
+        ];
 
-        let (cols, rows) = size().unwrap();
+        let (cols, rows) = backend.size();
         let center_x = cols / 2;
-        let start_y = rows / 4;
+        // Título, controles, objetivo e instrucción de inicio.
+        let height = controls.len() as u16 + 12;
+        let start_y = (rows / 4).min(rows.saturating_sub(height));
 
         // Título, controles e instrucciones.
-        stdout.execute(MoveTo(center_x.saturating_sub(15), start_y)).unwrap();
-        print!("{}", "🎮 LABERINTO DE RECOLECCIÓN 🎮".with(Color::Cyan).bold());
+        backend.print(center_x.saturating_sub(15), start_y, "🎮 LABERINTO DE RECOLECCIÓN 🎮".with(Color::Cyan).bold())?;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(10), start_y + 2)).unwrap();
-        print!("{}", "═══════════════════════".with(Color::DarkCyan));
+        backend.print(center_x.saturating_sub(10), start_y + 2, "═══════════════════════".with(Color::DarkCyan))?;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(10), start_y + 4)).unwrap();
-        print!("{}", "⌨️  CONTROLES:".with(Color::Yellow).bold());
+        backend.print(center_x.saturating_sub(10), start_y + 4, "⌨️  CONTROLES:".with(Color::Yellow).bold())?;
 
-        let controls = [
-            "WASD / ↑↓←→  - Mover jugador",
-            "Q / E        - Rotar cámara",
-            "C            - Cambiar vista",
-            "X / ESC      - Salir",
-        ];
-
-        for (i, control) in controls.iter().enumerate() {
-            stdout.execute(MoveTo(center_x.saturating_sub(15), start_y + 6 + i as u16)).unwrap();
-            print!("{}", control.with(Color::White));
+        for (i, (keys, description)) in controls.iter().enumerate() {
+            let control = format!("{:<12} - {}", keys, description);
+            backend.print(center_x.saturating_sub(15), start_y + 6 + i as u16, control.as_str().with(Color::White))?;
         }
 
-        stdout.execute(MoveTo(center_x.saturating_sub(10), start_y + 11)).unwrap();
-        print!("{}", "🎯 OBJETIVO:".with(Color::Yellow).bold());
+        let objective_y = start_y + 7 + controls.len() as u16;
+        backend.print(center_x.saturating_sub(10), objective_y, "🎯 OBJETIVO:".with(Color::Yellow).bold())?;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(22), start_y + 12)).unwrap();
-        print!("{}", "Recolecta ◆ y llega a la meta 🟥 en 3 niveles".with(Color::White));
+        backend.print(center_x.saturating_sub(22), objective_y + 1, "Recolecta ◆ y llega a la meta 🟥 en cada nivel".with(Color::White))?;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(15), start_y + 15)).unwrap();
-        print!("{}", "Presiona ENTER para jugar".with(Color::Green).bold());
+        backend.print(center_x.saturating_sub(15), objective_y + 4, format!("Presiona {} para jugar", keymap.keys_label(Action::Confirm)).as_str().with(Color::Green).bold())?;
 
-        stdout.flush().unwrap();
+        backend.flush()
     }
 
     /// Muestra la pantalla de victoria cuando se completan todos los niveles.
-    pub fn show_victory(&self) {
-        let mut stdout = stdout();
-        stdout.execute(Clear(ClearType::All)).unwrap();
+    pub fn show_victory(&mut self, backend: &mut dyn Backend) -> io::Result<()> {
+        backend.clear()?;
+        self.presenter.invalidate();
 
-        let (cols, rows) = size().unwrap();
+        let (cols, rows) = backend.size();
         let center_x = cols / 2;
         let center_y = rows / 2;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(15), center_y.saturating_sub(3))).unwrap();
-        print!("{}", "🎉 ¡FELICIDADES! 🎉".with(Color::Green).bold());
+        backend.print(center_x.saturating_sub(15), center_y.saturating_sub(3), "🎉 ¡FELICIDADES! 🎉".with(Color::Green).bold())?;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(18), center_y.saturating_sub(1))).unwrap();
-        print!("{}", "¡Has completado todos los niveles!".with(Color::Yellow));
+        backend.print(center_x.saturating_sub(18), center_y.saturating_sub(1), "¡Has completado todos los niveles!".with(Color::Yellow))?;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(18), center_y + 1)).unwrap();
-        print!("{}", "Presiona cualquier tecla para salir".with(Color::White));
+        backend.print(center_x.saturating_sub(18), center_y + 1, "Presiona cualquier tecla para salir".with(Color::White))?;
 
-        stdout.flush().unwrap();
+        backend.flush()
+    }
+
+    /// Muestra un aviso cuando la terminal es demasiado pequeña para el mapa actual.
+    pub fn show_size_warning(&mut self, backend: &mut dyn Backend) -> io::Result<()> {
+        backend.clear()?;
+        self.presenter.invalidate();
+
+        let (cols, rows) = backend.size();
+        let (min_cols, min_rows) = self.minimum_terminal_size();
+        let center_x = cols / 2;
+        let center_y = rows / 2;
+
+        let current = format!("Actual: {}×{}", cols, rows);
+        let minimum = format!("Mínimo: {}×{}", min_cols, min_rows);
+        let quit = format!("{} para salir", self.keymap.keys_label(Action::Quit));
+        let lines = [
+            "Terminal demasiado pequeña".with(Color::Red).bold(),
+            current.as_str().with(Color::Yellow),
+            minimum.as_str().with(Color::Yellow),
+            "Agranda la ventana".with(Color::White),
+            quit.as_str().with(Color::DarkGrey),
+        ];
+
+        let top = center_y.saturating_sub(lines.len() as u16 / 2);
+        for (i, line) in lines.into_iter().enumerate() {
+            let width = line.content().chars().count() as u16;
+            backend.print(center_x.saturating_sub(width / 2), top + i as u16, line)?;
+        }
+
+        backend.flush()
     }
 
     /// Renderiza la vista cenital (Top-Down) del juego.
     pub fn render_top_down(
-        &mut self,
+        &self,
         framebuffer: &mut Framebuffer,
-        map: &[Vec<u8>],
+        map: &[Vec<Cell>],
         player: &Player,
-        _entities: &[Entity],
+        entities: &[Entity],
     ) {
         framebuffer.clear(Color::Black);
 
-        let (px, py) = player.get_grid_position();
-        if map[py][px] == 5 { // Si el jugador está sobre un ítem, lo marca como recogido.
-            self.collected_positions.insert((px, py));
-        }
-
         let map_width = map[0].len();
         let map_height = map.len();
         
-        // Dibuja el borde del mapa.
-        for x in 0..map_width * self.cell_width {
-            framebuffer.set_pixel(x, 0, Pixel::new(Color::DarkCyan, '═'));
-        }
-        for x in 0..map_width * self.cell_width {
-            let y = map_height * self.cell_height - 1;
-            if y < framebuffer.height {
-                framebuffer.set_pixel(x, y, Pixel::new(Color::DarkCyan, '═'));
-            }
-        }
-        for y in 0..map_height * self.cell_height {
-            if y < framebuffer.height {
-                framebuffer.set_pixel(0, y, Pixel::new(Color::DarkCyan, '║'));
-                let x = map_width * self.cell_width - 1;
-                if x < framebuffer.width {
-                    framebuffer.set_pixel(x, y, Pixel::new(Color::DarkCyan, '║'));
-                }
-            }
-        }
-        framebuffer.set_pixel(0, 0, Pixel::new(Color::DarkCyan, '╔'));
-        framebuffer.set_pixel(map_width * self.cell_width - 1, 0, Pixel::new(Color::DarkCyan, '╗'));
-        let bottom_left_y = map_height * self.cell_height - 1;
-        if bottom_left_y < framebuffer.height {
-            framebuffer.set_pixel(0, bottom_left_y, Pixel::new(Color::DarkCyan, '╚'));
-            framebuffer.set_pixel(map_width * self.cell_width - 1, bottom_left_y, Pixel::new(Color::DarkCyan, '╝'));
+        if self.braille {
+            self.render_top_down_braille(framebuffer, map, player, entities);
+        } else {
+            self.render_top_down_cells(framebuffer, map, player, entities);
         }
+        self.render_map_border(framebuffer, map_width, map_height);
+    }
+
+    /// Dibuja el mapa, el jugador y los rayos de visión con un carácter por píxel.
+    fn render_top_down_cells(&self, framebuffer: &mut Framebuffer, map: &[Vec<Cell>], player: &Player, entities: &[Entity]) {
+        let (px, py) = player.get_grid_position();
 
         // Renderiza el contenido del mapa.
         for (row_idx, fila) in map.iter().enumerate() {
             for (col_idx, &celda) in fila.iter().enumerate() {
-                let actual_cell = if celda == 5 && self.collected_positions.contains(&(col_idx, row_idx)) {
-                    0 // Si el ítem fue recogido, se muestra como espacio vacío.
-                } else {
-                    celda
-                };
-
-                let pixel = match actual_cell {
-                    0 => Pixel::new(Color::Black, ' '),
-                    1 => Pixel::new(Color::White, '█'),
-                    2 => Pixel::new(Color::Green, '▓'),
-                    3 => Pixel::new(Color::Red, '▓'),
-                    5 => Pixel::new(Color::Yellow, '◆'),
-                    _ => Pixel::new(Color::Red, '?'),
-                };
+                let pixel = celda.pixel();
 
                 // Añade textura a las paredes para un efecto visual.
-                let enhanced_pixel = if actual_cell == 1 {
+                let enhanced_pixel = if celda == Cell::Wall {
                     if (col_idx + row_idx) % 3 == 0 {
                         Pixel::new(Color::Grey, '█')
                     } else {
@@ -216,6 +431,21 @@ impl GameRenderer {
             }
         }
 
+        // Las entidades ocupan un bloque del tamaño de una celda centrado en su
+        // posición, que se mueve con ellas entre celdas.
+        for entity in entities.iter().filter(|entity| entity.entity_type != EntityType::Player) {
+            let left = ((entity.position.x - 0.5) * self.cell_width as f32).round() as isize;
+            let top = ((entity.position.y - 0.5) * self.cell_height as f32).round() as isize;
+            for sub_row in 0..self.cell_height as isize {
+                for sub_col in 0..self.cell_width as isize {
+                    let (fb_x, fb_y) = (left + sub_col, top + sub_row);
+                    if fb_x > 0 && fb_x < framebuffer.width as isize - 1 && fb_y > 0 && fb_y < framebuffer.height as isize - 1 {
+                        framebuffer.set_pixel(fb_x as usize, fb_y as usize, entity.pixel);
+                    }
+                }
+            }
+        }
+
         // Renderiza al jugador y su dirección.
         let player_center_x = px * self.cell_width + self.cell_width / 2;
         let player_center_y = py * self.cell_height + self.cell_height / 2;
@@ -246,43 +476,155 @@ impl GameRenderer {
         self.render_vision_rays(framebuffer, player, map);
     }
 
+    /// Dibuja el borde del mapa en la vista cenital.
+    fn render_map_border(&self, framebuffer: &mut Framebuffer, map_width: usize, map_height: usize) {
+        for x in 0..map_width * self.cell_width {
+            framebuffer.set_pixel(x, 0, Pixel::new(Color::DarkCyan, '═'));
+        }
+        for x in 0..map_width * self.cell_width {
+            let y = map_height * self.cell_height - 1;
+            if y < framebuffer.height {
+                framebuffer.set_pixel(x, y, Pixel::new(Color::DarkCyan, '═'));
+            }
+        }
+        for y in 0..map_height * self.cell_height {
+            if y < framebuffer.height {
+                framebuffer.set_pixel(0, y, Pixel::new(Color::DarkCyan, '║'));
+                let x = map_width * self.cell_width - 1;
+                if x < framebuffer.width {
+                    framebuffer.set_pixel(x, y, Pixel::new(Color::DarkCyan, '║'));
+                }
+            }
+        }
+        framebuffer.set_pixel(0, 0, Pixel::new(Color::DarkCyan, '╔'));
+        framebuffer.set_pixel(map_width * self.cell_width - 1, 0, Pixel::new(Color::DarkCyan, '╗'));
+        let bottom_left_y = map_height * self.cell_height - 1;
+        if bottom_left_y < framebuffer.height {
+            framebuffer.set_pixel(0, bottom_left_y, Pixel::new(Color::DarkCyan, '╚'));
+            framebuffer.set_pixel(map_width * self.cell_width - 1, bottom_left_y, Pixel::new(Color::DarkCyan, '╝'));
+        }
+    }
+
+    /// Dibuja el mapa, el jugador y los rayos de visión sobre un lienzo Braille,
+    /// con 2×4 puntos por carácter.
+    fn render_top_down_braille(&self, framebuffer: &mut Framebuffer, map: &[Vec<Cell>], player: &Player, entities: &[Entity]) {
+        let mut canvas = BrailleCanvas::new(framebuffer.width, framebuffer.height);
+        // Puntos por celda del mapa en cada eje.
+        let scale_x = (self.cell_width * 2) as f32;
+        let scale_y = (self.cell_height * 4) as f32;
+        let center_x = player.position.x * scale_x;
+        let center_y = player.position.y * scale_y;
+
+        // Los rayos van primero para que las paredes y los objetos queden encima.
+        let ray_count = 15;
+        for i in 0..ray_count {
+            let angle_offset = (i as f32 / (ray_count - 1) as f32 - 0.5) * player.fov;
+            let ray_dir = player.direction.rotate(angle_offset);
+            let distance = cast_ray(player.position, ray_dir, map).distance;
+            let end_x = (player.position.x + ray_dir.x * distance) * scale_x;
+            let end_y = (player.position.y + ray_dir.y * distance) * scale_y;
+            canvas.line(center_x, center_y, end_x, end_y, Color::DarkYellow);
+        }
+
+        for (row_idx, fila) in map.iter().enumerate() {
+            for (col_idx, &cell) in fila.iter().enumerate() {
+                let x = col_idx as f32 * scale_x;
+                let y = row_idx as f32 * scale_y;
+
+                match cell {
+                    Cell::Floor => {}
+                    Cell::Item | Cell::Key(_) => canvas.fill_ellipse(
+                        x + scale_x / 2.0,
+                        y + scale_y / 2.0,
+                        scale_x * 0.2,
+                        scale_y * 0.2,
+                        cell.color(),
+                    ),
+                    // La hoja de la puerta se desliza hacia un lado al abrirse.
+                    Cell::Door { .. } => {
+                        let opening = cell.door_opening().unwrap_or(0.0);
+                        canvas.fill_rect(x + scale_x * opening, y, scale_x * (1.0 - opening), scale_y, cell.color());
+                    }
+                    Cell::Teleporter(_) => canvas.fill_ellipse(
+                        x + scale_x / 2.0,
+                        y + scale_y / 2.0,
+                        scale_x * 0.35,
+                        scale_y * 0.35,
+                        cell.color(),
+                    ),
+                    // Una flecha de un borde a otro de la celda, en el sentido de la cinta.
+                    Cell::OneWay(heading) => {
+                        let dir = heading.vector();
+                        let (cx, cy) = (x + scale_x / 2.0, y + scale_y / 2.0);
+                        let (hx, hy) = (dir.x * scale_x * 0.4, dir.y * scale_y * 0.4);
+                        let (tip_x, tip_y) = (cx + hx, cy + hy);
+                        canvas.line(cx - hx, cy - hy, tip_x, tip_y, cell.color());
+                        // Las puntas de la flecha, hacia atrás y a los lados.
+                        let (sx, sy) = (-dir.y * scale_x * 0.3, dir.x * scale_y * 0.3);
+                        canvas.line(tip_x, tip_y, cx + sx, cy + sy, cell.color());
+                        canvas.line(tip_x, tip_y, cx - sx, cy - sy, cell.color());
+                    }
+                    // El inicio y la meta se dibujan un poco más pequeños que una celda.
+                    Cell::Start | Cell::Goal => canvas.fill_rect(
+                        x + scale_x * 0.2,
+                        y + scale_y * 0.2,
+                        scale_x * 0.6,
+                        scale_y * 0.6,
+                        cell.color(),
+                    ),
+                    _ => canvas.fill_rect(x, y, scale_x, scale_y, cell.color()),
+                }
+            }
+        }
+
+        for entity in entities.iter().filter(|entity| entity.entity_type != EntityType::Player) {
+            canvas.fill_ellipse(
+                entity.position.x * scale_x,
+                entity.position.y * scale_y,
+                scale_x * 0.3,
+                scale_y * 0.3,
+                entity.pixel.color,
+            );
+        }
+
+        // El jugador es un círculo con una línea que indica hacia dónde mira.
+        canvas.fill_ellipse(center_x, center_y, scale_x * 0.25, scale_y * 0.25, Color::Cyan);
+        canvas.line(
+            center_x,
+            center_y,
+            center_x + player.direction.x * scale_x * 0.8,
+            center_y + player.direction.y * scale_y * 0.8,
+            Color::Yellow,
+        );
+
+        canvas.draw(framebuffer, 0, 0);
+    }
+
     /// Renderiza la vista en primera persona (First-Person) del juego.
     pub fn render_first_person(
         &mut self,
         framebuffer: &mut Framebuffer,
         player: &Player,
-        map: &[Vec<u8>],
+        map: &[Vec<Cell>],
+        entities: &[Entity],
     ) {
-        let (px, py) = player.get_grid_position();
-        if map[py][px] == 5 {
-            self.collected_positions.insert((px, py));
-        }
-
-        // Dibuja el cielo y el suelo con gradientes de color.
-        for y in 0..framebuffer.height / 2 {
-            for x in 0..framebuffer.width {
-                let color = if y < framebuffer.height / 6 { Color::DarkBlue } else if y < framebuffer.height / 4 { Color::Blue } else { Color::DarkCyan };
-                framebuffer.set_pixel(x, y, Pixel::new(color, '█'));
-            }
-        }
-        for y in framebuffer.height / 2..framebuffer.height {
-            for x in 0..framebuffer.width {
-                let color = if y > framebuffer.height * 4 / 5 { Color::Black } else if y > framebuffer.height * 7 / 10 { Color::DarkGrey } else { Color::DarkGreen };
-                framebuffer.set_pixel(x, y, Pixel::new(color, '█'));
-            }
-        }
+        self.render_floor_and_ceiling(framebuffer, player, map);
 
         // Lanzamiento de rayos (Raycasting) para renderizar las paredes.
         let ray_count = framebuffer.width;
         let ray_angle_step = player.fov / ray_count as f32;
         let start_angle = -player.fov / 2.0;
 
+        self.depth_buffer.clear();
+        self.depth_buffer.resize(ray_count, f32::INFINITY);
+
         for i in 0..ray_count {
             let ray_angle = start_angle + i as f32 * ray_angle_step;
             let ray_dir = player.direction.rotate(ray_angle);
 
-            let (distance, hit_type) = self.cast_ray_with_type(player.position, ray_dir, map);
-            let corrected_distance = distance * ray_angle.cos(); // Corrige la distorsión de ojo de pez.
+            let hit = cast_ray(player.position, ray_dir, map);
+            let corrected_distance = hit.distance * ray_angle.cos(); // Corrige la distorsión de ojo de pez.
+            self.depth_buffer[i] = corrected_distance;
 
             let line_height = if corrected_distance > 0.0 {
                 (framebuffer.height as f32 / corrected_distance) as usize
@@ -293,22 +635,146 @@ impl GameRenderer {
             let draw_start = if line_height < framebuffer.height { (framebuffer.height - line_height) / 2 } else { 0 };
             let draw_end = if line_height < framebuffer.height { (framebuffer.height + line_height) / 2 } else { framebuffer.height - 1 };
 
-            let base_color = match hit_type { 1 => Color::White, _ => Color::Grey };
+            // Atenúa el color de las paredes según la distancia y la orientación de la cara.
+            let light = hit.face.light();
+
+            match self.textures.get(hit.cell) {
+                Some(texture) => {
+                    // La pared ocupa `line_height` filas centradas, aunque se salga de la pantalla.
+                    let wall_top = (framebuffer.height as f32 - line_height as f32) / 2.0;
+                    for y in draw_start..=draw_end.min(framebuffer.height - 1) {
+                        let v = ((y as f32 + 0.5 - wall_top) / line_height.max(1) as f32).clamp(0.0, 1.0);
+                        let texel = texture.sample(hit.wall_x, v);
+                        let color = shade(texel.color, corrected_distance, light);
+                        framebuffer.set_pixel(i, y, Pixel::rgb(color, texel.symbol));
+                    }
+                }
+                None => {
+                    let color = shade(hit.cell.color(), corrected_distance, light);
+                    let symbol = if corrected_distance < 3.0 { '█' } else { '▓' };
+                    for y in draw_start..=draw_end.min(framebuffer.height - 1) {
+                        framebuffer.set_pixel(i, y, Pixel::rgb(color, symbol));
+                    }
+                }
+            }
+        }
+
+        // Reúne los sprites de las celdas del mapa y de las entidades.
+        let mut sprites: Vec<Sprite> = map
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &cell)| (x, y, cell)))
+            .filter_map(|(x, y, cell)| Sprite::from_cell(x, y, cell))
+            .chain(entities.iter().filter_map(Sprite::from_entity))
+            .collect();
+        self.render_sprites(framebuffer, player, &mut sprites);
+
+        self.render_minimap(framebuffer, player, map, entities);
+    }
+
+    /// Dibuja el suelo y el techo proyectando cada fila de la pantalla sobre el
+    /// mapa, de modo que las baldosas se mueven con la cámara y el suelo marca el
+    /// inicio, la meta y las casillas con ítems.
+    fn render_floor_and_ceiling(&self, framebuffer: &mut Framebuffer, player: &Player, map: &[Vec<Cell>]) {
+        let height = framebuffer.height as f32;
+        let ray_angle_step = player.fov / framebuffer.width as f32;
+        let start_angle = -player.fov / 2.0;
+
+        // Direcciones de cada columna, calculadas una vez para todas las filas.
+        let columns: Vec<(Vec2, f32)> = (0..framebuffer.width)
+            .map(|i| {
+                let ray_angle = start_angle + i as f32 * ray_angle_step;
+                (player.direction.rotate(ray_angle), ray_angle.cos())
+            })
+            .collect();
 
-            // Atenúa el color de las paredes según la distancia.
-            let color = if corrected_distance < 2.0 { base_color } else if corrected_distance < 4.0 { Color::Grey } else if corrected_distance < 8.0 { Color::DarkGrey } else { Color::Black };
-            let symbol = if corrected_distance < 3.0 { '█' } else { '▓' };
+        for y in framebuffer.height / 2..framebuffer.height {
+            // Distancia perpendicular a la que una pared tendría su base en esta fila;
+            // el techo de la fila simétrica está a la misma distancia.
+            let row_distance = height / (2.0 * y as f32 + 1.0 - height);
+            let ceiling_y = framebuffer.height - 1 - y;
+
+            for (x, &(ray_dir, cos_angle)) in columns.iter().enumerate() {
+                let distance = row_distance / cos_angle;
+                let world_x = player.position.x + ray_dir.x * distance;
+                let world_y = player.position.y + ray_dir.y * distance;
+                if world_x < 0.0 || world_y < 0.0 {
+                    continue;
+                }
+                let (cell_x, cell_y) = (world_x as usize, world_y as usize);
+                let Some(&cell) = map.get(cell_y).and_then(|row| row.get(cell_x)) else {
+                    continue;
+                };
 
-            for y in draw_start..=draw_end.min(framebuffer.height - 1) {
-                framebuffer.set_pixel(i, y, Pixel::new(color, symbol));
+                // Las baldosas alternan de símbolo como un tablero de ajedrez.
+                let checker = (cell_x + cell_y) % 2 == 0;
+                let floor = shade(cell.floor_color(), distance, 1.0);
+                let ceiling = shade(Color::Blue, distance, 1.0);
+                framebuffer.set_pixel(x, y, Pixel::rgb(floor, if checker { '▒' } else { '░' }));
+                if ceiling_y != y {
+                    framebuffer.set_pixel(x, ceiling_y, Pixel::rgb(ceiling, if checker { '▓' } else { '▒' }));
+                }
             }
         }
+    }
+
+    /// Dibuja los sprites como billboards, del más lejano al más cercano, usando el
+    /// búfer de profundidad para ocultar las partes que quedan detrás de las paredes.
+    fn render_sprites(&self, framebuffer: &mut Framebuffer, player: &Player, sprites: &mut [Sprite]) {
+        let ray_angle_step = player.fov / framebuffer.width as f32;
+        let distance_to = |sprite: &Sprite| {
+            let dx = sprite.position.x - player.position.x;
+            let dy = sprite.position.y - player.position.y;
+            dx * dx + dy * dy
+        };
+        sprites.sort_by(|a, b| distance_to(b).total_cmp(&distance_to(a)));
+
+        for sprite in sprites.iter() {
+            let relative = Vec2::new(
+                sprite.position.x - player.position.x,
+                sprite.position.y - player.position.y,
+            );
+            let distance = relative.length();
+
+            // Ángulo del sprite respecto a la dirección de la mirada, en el mismo
+            // sentido en que se recorren los rayos de izquierda a derecha.
+            let angle = (player.direction.x * relative.y - player.direction.y * relative.x)
+                .atan2(player.direction.x * relative.x + player.direction.y * relative.y);
+            let corrected_distance = distance * angle.cos();
+            if corrected_distance < 0.2 || angle.abs() > player.fov {
+                continue;
+            }
 
-        self.render_minimap(framebuffer, player, map);
+            // El tamaño en pantalla se calcula igual que para una pared de una celda de ancho.
+            let wall_height = framebuffer.height as f32 / corrected_distance;
+            let sprite_height = (wall_height * sprite.scale).max(1.0);
+            let half_width_angle = (0.5 * sprite.scale / distance).atan();
+
+            let center_column = (angle + player.fov / 2.0) / ray_angle_step;
+            let half_columns = (half_width_angle / ray_angle_step).max(0.5);
+            let first_column = (center_column - half_columns).round().max(0.0) as usize;
+            let last_column = ((center_column + half_columns).round() as isize).min(framebuffer.width as isize - 1);
+
+            // El sprite se apoya en el suelo, a la altura de la base de las paredes.
+            let floor_y = (framebuffer.height as f32 + wall_height) / 2.0;
+            let draw_start = (floor_y - sprite_height).max(0.0) as usize;
+            let draw_end = (floor_y.min(framebuffer.height as f32) as usize).max(draw_start + 1);
+
+            for column in first_column as isize..=last_column {
+                let column = column as usize;
+                if corrected_distance >= self.depth_buffer[column] {
+                    continue; // Oculto detrás de una pared.
+                }
+                let pixel = Pixel::rgb(shade(sprite.pixel.color, corrected_distance, 1.0), sprite.pixel.symbol);
+                for y in draw_start..draw_end {
+                    framebuffer.set_pixel(column, y, pixel);
+                }
+            }
+        }
     }
 
     /// Renderiza el minimapa en la esquina de la pantalla.
-    fn render_minimap(&self, framebuffer: &mut Framebuffer, player: &Player, map: &[Vec<u8>]) {
+    fn render_minimap(&self, framebuffer: &mut Framebuffer, player: &Player, map: &[Vec<Cell>], entities: &[Entity]) {
         let total_minimap_width = self.minimap_size + 2;
         let total_minimap_height = self.minimap_size + 2;
 
@@ -347,22 +813,16 @@ impl GameRenderer {
 
                 if screen_x >= framebuffer.width || screen_y >= framebuffer.height { continue; }
 
+                let entity = entities.iter().find(|entity| {
+                    entity.entity_type != EntityType::Player
+                        && (entity.position.x as usize, entity.position.y as usize) == (world_x, world_y)
+                });
                 let pixel = if world_x == player_map_x && world_y == player_map_y {
                     Pixel::new(Color::Cyan, '●')
+                } else if let Some(entity) = entity {
+                    entity.pixel
                 } else if world_y < map.len() && world_x < map[0].len() {
-                    let cell = map[world_y][world_x];
-                    if cell == 5 && self.collected_positions.contains(&(world_x, world_y)) {
-                        Pixel::new(Color::Black, ' ')
-                    } else {
-                        match cell {
-                            0 => Pixel::new(Color::Black, ' '),
-                            1 => Pixel::new(Color::White, '█'),
-                            2 => Pixel::new(Color::Green, '▓'),
-                            3 => Pixel::new(Color::Red, '▓'),
-                            5 => Pixel::new(Color::Yellow, '◆'),
-                            _ => Pixel::new(Color::Black, '?'),
-                        }
-                    }
+                    map[world_y][world_x].pixel()
                 } else {
                     Pixel::new(Color::Black, ' ')
                 };
@@ -377,13 +837,13 @@ impl GameRenderer {
         let dir_x = (center_x as f32 + player.direction.x * 2.0).round() as usize;
         let dir_y = (center_y as f32 + player.direction.y * 2.0).round() as usize;
 
-        if dir_x >= minimap_x + 1 && dir_x < minimap_x + total_minimap_width - 1 && dir_y >= minimap_y + 1 && dir_y < minimap_y + total_minimap_height - 1 && dir_x < framebuffer.width && dir_y < framebuffer.height {
+        if dir_x > minimap_x && dir_x < minimap_x + total_minimap_width - 1 && dir_y > minimap_y && dir_y < minimap_y + total_minimap_height - 1 && dir_x < framebuffer.width && dir_y < framebuffer.height {
             framebuffer.set_pixel(dir_x, dir_y, Pixel::new(Color::Red, '▲'));
         }
     }
 
     /// Renderiza los rayos de visión del jugador en la vista cenital.
-    fn render_vision_rays(&self, framebuffer: &mut Framebuffer, player: &Player, map: &[Vec<u8>]) {
+    fn render_vision_rays(&self, framebuffer: &mut Framebuffer, player: &Player, map: &[Vec<Cell>]) {
         let center_x = (player.position.x * self.cell_width as f32) as usize;
         let center_y = (player.position.y * self.cell_height as f32) as usize;
 
@@ -391,7 +851,7 @@ impl GameRenderer {
             let angle_offset = (i as f32 - 2.0) * (player.fov / 4.0);
             let ray_dir = player.direction.rotate(angle_offset);
 
-            let (distance, _) = self.cast_ray_with_type(player.position, ray_dir, map);
+            let distance = cast_ray(player.position, ray_dir, map).distance;
             let end_x = player.position.x + ray_dir.x * distance;
             let end_y = player.position.y + ray_dir.y * distance;
 
@@ -405,107 +865,101 @@ impl GameRenderer {
         }
     }
 
-    /// Lanza un rayo desde una posición y en una dirección, y devuelve la distancia al primer obstáculo y el tipo de celda golpeada.
-    fn cast_ray_with_type(&self, start: Vec2, direction: Vec2, map: &[Vec<u8>]) -> (f32, u8) {
-        let mut map_x = start.x as i32;
-        let mut map_y = start.y as i32;
-
-        let delta_dist_x = (1.0 / direction.x).abs();
-        let delta_dist_y = (1.0 / direction.y).abs();
-
-        let (step_x, mut side_dist_x) = if direction.x < 0.0 {
-            (-1, (start.x - map_x as f32) * delta_dist_x)
-        } else {
-            (1, (map_x as f32 + 1.0 - start.x) * delta_dist_x)
-        };
-
-        let (step_y, mut side_dist_y) = if direction.y < 0.0 {
-            (-1, (start.y - map_y as f32) * delta_dist_y)
-        } else {
-            (1, (map_y as f32 + 1.0 - start.y) * delta_dist_y)
-        };
-
-        let mut hit = false;
-        let mut side = 0;
-        let mut hit_type = 0u8;
-
-        while !hit {
-            if side_dist_x < side_dist_y {
-                side_dist_x += delta_dist_x;
-                map_x += step_x;
-                side = 0;
-            } else {
-                side_dist_y += delta_dist_y;
-                map_y += step_y;
-                side = 1;
-            }
-
-            if map_x < 0 || map_y < 0 || map_y as usize >= map.len() || map_x as usize >= map[0].len() {
-                hit = true;
-                hit_type = 1;
-            } else {
-                let cell = map[map_y as usize][map_x as usize];
-                if cell == 1 {
-                    hit = true;
-                    hit_type = cell;
-                }
-            }
-        }
-
-        let perp_wall_dist = if side == 0 {
-            (map_x as f32 - start.x + (1 - step_x) as f32 / 2.0) / direction.x
-        } else {
-            (map_y as f32 - start.y + (1 - step_y) as f32 / 2.0) / direction.y
-        };
-
-        (perp_wall_dist.abs(), hit_type)
-    }
-
-    /// Muestra el contenido del framebuffer en la terminal.
-    pub fn display_framebuffer(&self, framebuffer: &Framebuffer) {
-        let mut stdout = stdout();
-        
-        let use_offset = framebuffer.width < size().unwrap().0 as usize;
-
-        for (y, row) in framebuffer.pixels.iter().enumerate() {
-            let x_pos = if use_offset { self.offset_x } else { 0 };
-            let y_pos = if use_offset { self.offset_y + y as u16 } else { y as u16 };
-
-            stdout.execute(MoveTo(x_pos, y_pos)).unwrap();
-
-            for pixel in row {
-                let styled = pixel.symbol.to_string().with(pixel.color);
-                print!("{}", styled);
+    /// Muestra el contenido del framebuffer en la terminal. Solo se escriben las
+    /// celdas que cambiaron desde el cuadro anterior.
+    pub fn display_framebuffer(&mut self, backend: &mut dyn Backend, framebuffer: &Framebuffer) -> io::Result<()> {
+        let use_offset = framebuffer.width < self.terminal_cols;
+        let (x_pos, y_pos) = if use_offset { (self.offset_x as usize, self.offset_y as usize) } else { (0, 0) };
+
+        self.presenter.clear();
+        for (y, rows) in framebuffer.pixels.chunks(self.pixels_per_row()).enumerate() {
+            for (x, top) in rows[0].iter().enumerate() {
+                let cell = if self.half_block {
+                    // El píxel de arriba es el primer plano de `▀` y el de abajo, el fondo.
+                    let bottom = rows.get(1).map_or(Color::Black, |row| row[x].fill_color());
+                    ScreenCell {
+                        symbol: '▀',
+                        foreground: self.color_mode.quantize(top.fill_color()),
+                        background: self.color_mode.quantize(bottom),
+                    }
+                } else {
+                    ScreenCell {
+                        symbol: top.symbol,
+                        foreground: self.color_mode.quantize(top.color),
+                        background: Color::Reset,
+                    }
+                };
+                self.presenter.put(x_pos + x, y_pos + y, cell);
             }
         }
-        stdout.flush().unwrap();
+        self.presenter.present(backend)
     }
 
     /// Muestra la interfaz de usuario (UI) con información del juego.
+    #[allow(clippy::too_many_arguments)]
     pub fn display_ui(
         &self,
+        backend: &mut dyn Backend,
         fps: f32,
-        px: f32,
-        py: f32,
+        player: &Player,
         collected: usize,
         required: usize,
         level: usize,
-        total_levels: usize,
+        total_levels: Option<usize>,
         level_name: &str,
-    ) {
-        let mut stdout = stdout();
-        let (_, rows) = size().unwrap();
-        
-        stdout.execute(MoveTo(0, rows.saturating_sub(3))).unwrap();
-        print!("{}", format!("[WASD:move | QE:rotate | C:camera | X:quit] FPS: {:.0}", fps).with(Color::White));
+        has_enemies: bool,
+    ) -> io::Result<()> {
+        let (cols, rows) = backend.size();
+        // Las líneas se recortan al ancho de la terminal para que no salten a la
+        // fila siguiente.
+        let width = cols as usize;
+
+        let first_keys = |actions: &[Action]| -> String {
+            actions
+                .iter()
+                .filter_map(|&action| self.keymap.keys(action).first())
+                .map(|&key| key_label(key))
+                .collect()
+        };
+        let controls = format!(
+            "[{}:move | {}:rotate | {}:camera | {}:half-block | {}:braille | {}:screenshot | {}:quit] FPS: {:.0}",
+            first_keys(&[Action::MoveForward, Action::StrafeLeft, Action::MoveBackward, Action::StrafeRight]),
+            first_keys(&[Action::TurnLeft, Action::TurnRight]),
+            first_keys(&[Action::ToggleCamera]),
+            first_keys(&[Action::ToggleHalfBlock]),
+            first_keys(&[Action::ToggleBraille]),
+            first_keys(&[Action::Screenshot]),
+            first_keys(&[Action::Quit]),
+            fps
+        );
+        backend.print(0, rows.saturating_sub(3), format!("{:.width$}", controls).as_str().with(Color::White))?;
         
-        stdout.execute(MoveTo(0, rows.saturating_sub(2))).unwrap();
-        print!("{}", format!(
+        let total = total_levels.map_or("∞".to_string(), |total| total.to_string());
+        let mut status = format!(
             "Nivel {}/{}: {} | Items: {}/{} ◆ | Pos: ({:.1}, {:.1})",
-            level, total_levels, level_name, collected, required, px, py
-        ).with(Color::Yellow));
+            level, total, level_name, collected, required, player.position.x, player.position.y
+        );
+        // Las vidas solo importan en los niveles con enemigos.
+        if has_enemies {
+            status.push_str(&format!(" | Vidas: {}", player.health));
+        }
+        let keys = player.inventory.keys();
+        if !keys.is_empty() {
+            let names: Vec<&str> = keys.iter().map(|color| color.name()).collect();
+            status.push_str(&format!(" | Llaves: {}", names.join(", ")));
+        }
+        backend.print(0, rows.saturating_sub(2), format!("{:.width$}", status).as_str().with(Color::Yellow))?;
         
-        stdout.flush().unwrap();
+        backend.flush()
+    }
+
+    /// Muestra un aviso en la última fila de la pantalla, debajo de la UI.
+    /// Un texto vacío borra el aviso anterior.
+    pub fn display_notice(&self, backend: &mut dyn Backend, text: &str) -> io::Result<()> {
+        let (cols, rows) = backend.size();
+        let line = format!("{:<width$.width$}", text, width = cols as usize);
+        backend.print(0, rows.saturating_sub(1), line.as_str().with(Color::Cyan))?;
+        backend.flush()
     }
 
     /// Devuelve el ancho de celda calculado.
@@ -518,44 +972,405 @@ impl GameRenderer {
         self.cell_height
     }
 
-    /// Muestra la pantalla de selección de nivel.
-    pub fn show_level_select(&self, selected: usize, levels: &[Level]) {
-        let mut stdout = stdout();
-        stdout.execute(Clear(ClearType::All)).unwrap();
+    /// Devuelve cuántos niveles caben en una página de la selección de nivel.
+    pub fn level_select_page_size(&self) -> usize {
+        Self::page_size_for_rows(self.terminal_rows as u16)
+    }
+
+    /// Calcula el tamaño de página para una terminal con `rows` filas, dejando
+    /// espacio para el título y las instrucciones.
+    fn page_size_for_rows(rows: u16) -> usize {
+        let start_y = rows / 4;
+        let free_rows = rows.saturating_sub(start_y + 12) as usize;
+        (free_rows / 2).clamp(1, 9)
+    }
+
+    /// Muestra la pantalla de selección de nivel, paginada según el tamaño de la terminal.
+    pub fn show_level_select(&mut self, backend: &mut dyn Backend, selected: usize, entries: &[LevelSelectEntry]) -> io::Result<()> {
+        backend.clear()?;
+        self.presenter.invalidate();
 
-        let (cols, rows) = size().unwrap();
+        let (cols, rows) = backend.size();
         let center_x = cols / 2;
         let start_y = rows / 4;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(12), start_y)).unwrap();
-        print!("{}", "🎯 SELECCIÓN DE NIVEL 🎯".with(Color::Cyan).bold());
+        let page_size = Self::page_size_for_rows(rows);
+        let page = selected / page_size;
+        let page_count = entries.len().div_ceil(page_size);
+        let first = page * page_size;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(10), start_y + 2)).unwrap();
-        print!("{}", "═══════════════════════".with(Color::DarkCyan));
+        backend.print(center_x.saturating_sub(12), start_y, "🎯 SELECCIÓN DE NIVEL 🎯".with(Color::Cyan).bold())?;
 
-        for (i, level) in levels.iter().enumerate() {
-            let y_pos = start_y + 4 + (i as u16 * 2);
-            stdout.execute(MoveTo(center_x.saturating_sub(20), y_pos)).unwrap();
-            
-            if i == selected {
-                print!("{}", format!("→ {} - {} items requeridos", level.name, level.required_items).with(Color::Yellow).bold());
+        backend.print(center_x.saturating_sub(10), start_y + 2, "═══════════════════════".with(Color::DarkCyan))?;
+
+        for (slot, (i, entry)) in entries.iter().enumerate().skip(first).take(page_size).enumerate() {
+            let y_pos = start_y + 4 + (slot as u16 * 2);
+            let marker = if i == selected { '→' } else { ' ' };
+            let text = format!("{} {}. {} - {}", marker, slot + 1, entry.name, entry.detail);
+
+            let styled = if i == selected {
+                text.as_str().with(Color::Yellow).bold()
+            } else if entry.unlocked {
+                text.as_str().with(Color::White)
             } else {
-                print!("{}", format!("  {} - {} items requeridos", level.name, level.required_items).with(Color::White));
+                text.as_str().with(Color::DarkGrey)
+            };
+            backend.print(center_x.saturating_sub(20), y_pos, styled)?;
+        }
+
+        let footer_y = start_y + 4 + page_size as u16 * 2;
+
+        if page_count > 1 {
+            backend.print(center_x.saturating_sub(6), footer_y, format!("Página {}/{}", page + 1, page_count).as_str().with(Color::Cyan))?;
+        }
+
+        let keymap = &self.keymap;
+        let select = format!("{}: Seleccionar nivel", keymap.columns_label(&[Action::MoveForward, Action::MoveBackward]));
+        backend.print(center_x.saturating_sub(18), footer_y + 2, select.as_str().with(Color::DarkGrey))?;
+
+        let pages = format!(
+            "1-{}: Ir a nivel de la página | {}/{}: Cambiar página",
+            page_size.min(entries.len() - first),
+            keymap.keys_label(Action::PageUp),
+            keymap.keys_label(Action::PageDown)
+        );
+        backend.print(center_x.saturating_sub(18), footer_y + 3, pages.as_str().with(Color::DarkGrey))?;
+
+        backend.print(center_x.saturating_sub(15), footer_y + 5, format!("Presiona {} para jugar", keymap.keys_label(Action::Confirm)).as_str().with(Color::Green).bold())?;
+
+        backend.print(center_x.saturating_sub(10), footer_y + 7, format!("{}: Volver al menú", keymap.keys_label(Action::Back)).as_str().with(Color::DarkGrey))?;
+
+        backend.flush()
+    }
+
+    /// Muestra la pantalla de configuración de teclas. Si la lista no cabe en la
+    /// terminal, se desplaza para que la entrada elegida siempre se vea.
+    /// `capturing` indica que se está esperando la nueva tecla de la entrada
+    /// elegida; `message` se muestra al pie, por ejemplo si no se pudo guardar.
+    pub fn show_bindings(
+        &mut self,
+        backend: &mut dyn Backend,
+        entries: &[BindingEntry],
+        selected: usize,
+        capturing: bool,
+        message: Option<&str>,
+    ) -> io::Result<()> {
+        backend.clear()?;
+        self.presenter.invalidate();
+
+        let (cols, rows) = backend.size();
+        let center_x = cols / 2;
+        let left = center_x.saturating_sub(25);
+
+        backend.print(center_x.saturating_sub(12), 1, "⌨️  CONFIGURAR TECLAS ⌨️".with(Color::Cyan).bold())?;
+
+        backend.print(center_x.saturating_sub(10), 2, "═══════════════════════".with(Color::DarkCyan))?;
+
+        // Título arriba y tres filas de ayuda abajo.
+        let visible = (rows as usize).saturating_sub(8).max(1);
+        let first = (selected + 1).saturating_sub(visible);
+        for (row, (i, entry)) in entries.iter().enumerate().skip(first).take(visible).enumerate() {
+            let marker = if i == selected { '→' } else { ' ' };
+            let keys = if i == selected && capturing { "[pulsa una tecla]" } else { entry.keys.as_str() };
+            let mut text = format!("{} {:<22} {}", marker, entry.label, keys);
+            if !entry.conflicts.is_empty() {
+                text.push_str(&format!("  ⚠ también: {}", entry.conflicts.join(", ")));
             }
+            let text = format!("{:.width$}", text, width = cols.saturating_sub(left) as usize);
+
+            let styled = if i == selected {
+                text.as_str().with(Color::Yellow).bold()
+            } else if !entry.conflicts.is_empty() {
+                text.as_str().with(Color::Red)
+            } else {
+                text.as_str().with(Color::White)
+            };
+            backend.print(left, 4 + row as u16, styled)?;
         }
 
-        stdout.execute(MoveTo(center_x.saturating_sub(18), start_y + 12)).unwrap();
-        print!("{}", "↑/↓ o W/S: Seleccionar nivel".with(Color::DarkGrey));
+        let footer_y = 5 + visible.min(entries.len()) as u16;
+        let help = if capturing {
+            "Pulsa la tecla que quieras añadir | ESC: Cancelar"
+        } else {
+            "↑/↓: Elegir | ENTER: Añadir tecla | RETROCESO: Borrar | ESC: Guardar y volver"
+        };
+        backend.print(left, footer_y, format!("{:.width$}", help, width = cols.saturating_sub(left) as usize).as_str().with(Color::DarkGrey))?;
 
-        stdout.execute(MoveTo(center_x.saturating_sub(18), start_y + 13)).unwrap();
-        print!("{}", "1/2/3: Ir a nivel directamente".with(Color::DarkGrey));
+        if let Some(message) = message {
+            backend.print(left, footer_y + 1, format!("{:.width$}", message, width = cols.saturating_sub(left) as usize).as_str().with(Color::Cyan))?;
+        }
 
-        stdout.execute(MoveTo(center_x.saturating_sub(15), start_y + 15)).unwrap();
-        print!("{}", "Presiona ENTER para jugar".with(Color::Green).bold());
+        backend.flush()
+    }
+}
 
-        stdout.execute(MoveTo(center_x.saturating_sub(10), start_y + 17)).unwrap();
-        print!("{}", "ESC: Volver al menú".with(Color::DarkGrey));
+/// Color hacia el que se funde todo lo que está lejos.
+const FOG_COLOR: Rgb = Rgb::new(0, 0, 0);
+/// Densidad de la niebla: a 8 celdas queda alrededor de un 13 % del color original.
+const FOG_DENSITY: f32 = 0.25;
+/// Atenuación de la luz con el cuadrado de la distancia.
+const LIGHT_FALLOFF: f32 = 0.02;
+
+/// Ilumina un color visto a la distancia indicada: lo multiplica por `light` y
+/// por la caída de la luz, y lo funde de forma continua con la niebla.
+fn shade(color: Color, distance: f32, light: f32) -> Rgb {
+    let distance = distance.max(0.0);
+    let brightness = light / (1.0 + LIGHT_FALLOFF * distance * distance);
+    let fog = 1.0 - (-FOG_DENSITY * distance).exp();
+    Rgb::from_color(color).scale(brightness).lerp(FOG_COLOR, fog)
+}
+
+/// Devuelve el tamaño del minimapa de la vista en primera persona para una
+/// terminal de `cols × rows` caracteres.
+fn minimap_size_for(cols: usize, rows: usize) -> usize {
+    14.min(cols / 6).min(rows / 6).max(10)
+}
+
+/// Lanza un rayo desde una posición y en una dirección, y devuelve la distancia al primer obstáculo, el tipo de celda golpeada y el punto exacto del impacto.
+/// Con una dirección unitaria, la distancia es la recorrida por el rayo.
+pub fn cast_ray(start: Vec2, direction: Vec2, map: &[Vec<Cell>]) -> RayHit {
+    let mut map_x = start.x as i32;
+    let mut map_y = start.y as i32;
+
+    let delta_dist_x = (1.0 / direction.x).abs();
+    let delta_dist_y = (1.0 / direction.y).abs();
+
+    let (step_x, mut side_dist_x) = if direction.x < 0.0 {
+        (-1, (start.x - map_x as f32) * delta_dist_x)
+    } else {
+        (1, (map_x as f32 + 1.0 - start.x) * delta_dist_x)
+    };
+
+    let (step_y, mut side_dist_y) = if direction.y < 0.0 {
+        (-1, (start.y - map_y as f32) * delta_dist_y)
+    } else {
+        (1, (map_y as f32 + 1.0 - start.y) * delta_dist_y)
+    };
+
+    // Distancia perpendicular hasta la cara de la celda `(map_x, map_y)` que
+    // cruza el rayo, y posición del cruce a lo largo de esa cara, en `[0, 1)`.
+    let face_hit = |map_x: i32, map_y: i32, side: i32| {
+        let distance = if side == 0 {
+            (map_x as f32 - start.x + (1 - step_x) as f32 / 2.0) / direction.x
+        } else {
+            (map_y as f32 - start.y + (1 - step_y) as f32 / 2.0) / direction.y
+        };
+        // Punto de impacto a lo largo de la cara: la coordenada que no cruzó el rayo.
+        let hit_point = if side == 0 {
+            start.y + distance * direction.y
+        } else {
+            start.x + distance * direction.x
+        };
+        (distance, hit_point - hit_point.floor())
+    };
+
+    let mut hit = false;
+    let mut side = 0;
+    let mut hit_type = Cell::Floor;
+
+    while !hit {
+        if side_dist_x < side_dist_y {
+            side_dist_x += delta_dist_x;
+            map_x += step_x;
+            side = 0;
+        } else {
+            side_dist_y += delta_dist_y;
+            map_y += step_y;
+            side = 1;
+        }
+
+        if map_x < 0 || map_y < 0 || map_y as usize >= map.len() || map_x as usize >= map[0].len() {
+            hit = true;
+            hit_type = Cell::Wall;
+        } else {
+            let cell = map[map_y as usize][map_x as usize];
+            // La hoja de una puerta se desliza al abrirse: el rayo pasa por
+            // la parte que ya quedó libre.
+            let through_door = cell
+                .door_opening()
+                .is_some_and(|opening| face_hit(map_x, map_y, side).1 < opening);
+            if cell.is_solid() && !through_door {
+                hit = true;
+                hit_type = cell;
+            }
+        }
+    }
+
+    let (perp_wall_dist, mut wall_x) = face_hit(map_x, map_y, side);
+    // La cara golpeada es la opuesta a la dirección en la que avanza el rayo.
+    let face = match (side, step_x, step_y) {
+        (0, 1, _) => WallFace::West,
+        (0, _, _) => WallFace::East,
+        (_, _, 1) => WallFace::North,
+        _ => WallFace::South,
+    };
+    // Invierte la coordenada en las caras cuya orientación la recorrería de
+    // derecha a izquierda, para que las texturas no se vean en espejo.
+    if let Some(opening) = hit_type.door_opening() {
+        // La textura de la puerta se desplaza con la hoja.
+        wall_x -= opening;
+    } else if matches!(face, WallFace::East | WallFace::North) {
+        wall_x = 1.0 - wall_x;
+    }
+
+    RayHit {
+        distance: perp_wall_dist.abs(),
+        cell: hit_type,
+        wall_x: wall_x.clamp(0.0, 0.999),
+        map_x,
+        map_y,
+        face,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Pruebas de imagen de referencia: cada pantalla se dibuja en un
+    //! `MemoryBackend` y se compara con su archivo en `tests/golden`. Con la
+    //! variable de entorno `UPDATE_GOLDEN` se reescriben los archivos en lugar
+    //! de compararlos.
+
+    use std::fmt::Write as _;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::game::{level_select_entries, Level, LevelPack};
+    use crate::graphics::MemoryBackend;
+
+    const COLS: u16 = 100;
+    const ROWS: u16 = 36;
+
+    fn levels_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("levels")
+    }
+
+    fn renderer_for(level: &Level) -> GameRenderer {
+        let mut renderer = GameRenderer::new(COLS as usize, ROWS as usize, level.get_width(), level.get_height());
+        renderer.set_textures(TextureSet::builtin());
+        renderer.set_color_mode(ColorMode::Ansi16);
+        renderer
+    }
+
+    /// Serializa la pantalla: el texto, y los colores de primer plano y de
+    /// fondo con una letra por celda, seguidos de la leyenda de las letras.
+    fn snapshot(backend: &MemoryBackend) -> String {
+        let mut palette: Vec<Color> = Vec::new();
+        let mut grid = |rows: Vec<Vec<Color>>| -> String {
+            rows.iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|color| {
+                            let index = palette.iter().position(|known| known == color).unwrap_or_else(|| {
+                                palette.push(*color);
+                                palette.len() - 1
+                            });
+                            char::from_digit(index as u32 % 36, 36).unwrap()
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let foreground = grid(backend.foreground_colors());
+        let background = grid(backend.background_colors());
+
+        let mut out = String::new();
+        writeln!(out, "## texto\n{}", backend.text()).unwrap();
+        writeln!(out, "## primer plano\n{}", foreground).unwrap();
+        writeln!(out, "## fondo\n{}", background).unwrap();
+        writeln!(out, "## colores").unwrap();
+        for (index, color) in palette.iter().enumerate() {
+            writeln!(out, "{} = {:?}", char::from_digit(index as u32 % 36, 36).unwrap(), color).unwrap();
+        }
+        out
+    }
+
+    /// Compara la pantalla con su imagen de referencia.
+    fn assert_golden(name: &str, backend: &MemoryBackend) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{}.txt", name));
+        let actual = snapshot(backend);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("{}: {} (genera el archivo con UPDATE_GOLDEN=1)", path.display(), err));
+        assert!(
+            actual == expected,
+            "la pantalla `{}` no coincide con {} (regenérala con UPDATE_GOLDEN=1 si el cambio es intencionado)",
+            name,
+            path.display()
+        );
+    }
+
+    fn first_level() -> Level {
+        Level::load(levels_dir().join("01_comienzo.txt")).unwrap()
+    }
+
+    #[test]
+    fn first_person_view() {
+        let level = first_level();
+        let mut renderer = renderer_for(&level);
+        let player = Player::from_map(&level.map);
+        let mut backend = MemoryBackend::new(COLS, ROWS);
+
+        let mut framebuffer = renderer.first_person_framebuffer();
+        renderer.render_first_person(&mut framebuffer, &player, &level.map, &[]);
+        renderer.display_framebuffer(&mut backend, &framebuffer).unwrap();
+        renderer
+            .display_ui(&mut backend, 0.0, &player, 0, level.required_items, 1, Some(6), &level.name, false)
+            .unwrap();
+
+        assert_golden("first_person", &backend);
+    }
+
+    #[test]
+    fn top_down_view() {
+        let level = first_level();
+        let mut renderer = renderer_for(&level);
+        let player = Player::from_map(&level.map);
+        let mut backend = MemoryBackend::new(COLS, ROWS);
+
+        let mut framebuffer = renderer.top_down_framebuffer();
+        renderer.render_top_down(&mut framebuffer, &level.map, &player, &[]);
+        renderer.display_framebuffer(&mut backend, &framebuffer).unwrap();
+        renderer
+            .display_ui(&mut backend, 0.0, &player, 0, level.required_items, 1, Some(6), &level.name, false)
+            .unwrap();
+
+        assert_golden("top_down", &backend);
+    }
+
+    #[test]
+    fn main_menu() {
+        let mut renderer = renderer_for(&first_level());
+        let mut backend = MemoryBackend::new(COLS, ROWS);
+        renderer.show_menu(&mut backend).unwrap();
+        assert_golden("menu", &backend);
+    }
+
+    #[test]
+    fn level_select_menu() {
+        let pack = LevelPack::load(levels_dir()).unwrap();
+        let levels = pack.load_levels().unwrap();
+        let completed = vec![false; levels.len()];
+        let entries = level_select_entries(&pack, &levels, &completed, Some(1));
+
+        let mut renderer = renderer_for(&levels[0]);
+        let mut backend = MemoryBackend::new(COLS, ROWS);
+        renderer.show_level_select(&mut backend, 1, &entries).unwrap();
+        assert_golden("level_select", &backend);
+    }
 
-        stdout.flush().unwrap();
+    #[test]
+    fn victory_screen() {
+        let mut renderer = renderer_for(&first_level());
+        let mut backend = MemoryBackend::new(COLS, ROWS);
+        renderer.show_victory(&mut backend).unwrap();
+        assert_golden("victory", &backend);
     }
 }
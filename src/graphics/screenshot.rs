@@ -0,0 +1,438 @@
+//! Capturas de pantalla de un `Framebuffer`.
+//!
+//! Una captura se puede guardar como texto con secuencias ANSI, como una página
+//! HTML o una imagen SVG con los caracteres coloreados, o como una imagen PPM
+//! en la que cada píxel del framebuffer es un bloque de color. Los colores se
+//! guardan siempre en RGB de 24 bits, sin convertirlos a lo que admita la
+//! terminal.
+
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::style::Color;
+
+use crate::graphics::{Framebuffer, Rgb};
+
+/// Ancho en puntos de cada carácter en las imágenes SVG.
+const SVG_CELL_WIDTH: usize = 8;
+/// Alto en puntos de cada carácter en las imágenes SVG.
+const SVG_CELL_HEIGHT: usize = 16;
+/// Ancho en puntos de cada píxel del framebuffer en las imágenes PPM.
+const PPM_BLOCK_SIZE: usize = 4;
+
+/// Los formatos en los que se puede guardar una captura.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScreenshotFormat {
+    /// Texto con secuencias de escape ANSI de color real.
+    Ansi,
+    /// Una página HTML independiente.
+    Html,
+    /// Una imagen SVG independiente.
+    Svg,
+    /// Una imagen PPM binaria (P6).
+    Ppm,
+}
+
+impl ScreenshotFormat {
+    /// Todos los formatos, en el orden en que se guardan con la tecla de captura.
+    pub const ALL: [ScreenshotFormat; 4] = [
+        ScreenshotFormat::Ansi,
+        ScreenshotFormat::Html,
+        ScreenshotFormat::Svg,
+        ScreenshotFormat::Ppm,
+    ];
+
+    /// Devuelve el formato que corresponde a la extensión de una ruta.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ans" | "txt" => Some(ScreenshotFormat::Ansi),
+            "html" | "htm" => Some(ScreenshotFormat::Html),
+            "svg" => Some(ScreenshotFormat::Svg),
+            "ppm" => Some(ScreenshotFormat::Ppm),
+            _ => None,
+        }
+    }
+
+    /// Devuelve la extensión de archivo habitual del formato.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Ansi => "ans",
+            ScreenshotFormat::Html => "html",
+            ScreenshotFormat::Svg => "svg",
+            ScreenshotFormat::Ppm => "ppm",
+        }
+    }
+}
+
+/// Error producido al guardar una captura.
+#[derive(Debug)]
+pub enum ScreenshotError {
+    /// No se pudo escribir el archivo.
+    Io(PathBuf, io::Error),
+    /// La extensión del archivo no corresponde a ningún formato.
+    UnknownFormat(PathBuf),
+}
+
+impl fmt::Display for ScreenshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenshotError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ScreenshotError::UnknownFormat(path) => write!(
+                f,
+                "{}: formato de captura desconocido (se admiten .ans, .html, .svg y .ppm)",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScreenshotError {}
+
+/// Una celda de la captura: un carácter con su color de primer plano y de
+/// fondo, o sin fondo.
+struct ShotCell {
+    symbol: char,
+    foreground: Rgb,
+    background: Option<Rgb>,
+}
+
+/// Una captura de un framebuffer tal como se ve en la terminal.
+pub struct Screenshot<'a> {
+    framebuffer: &'a Framebuffer,
+    half_block: bool,
+}
+
+impl<'a> Screenshot<'a> {
+    /// Crea una captura de un framebuffer en el que cada píxel es un carácter.
+    pub fn new(framebuffer: &'a Framebuffer) -> Self {
+        Self {
+            framebuffer,
+            half_block: false,
+        }
+    }
+
+    /// Indica si el framebuffer se muestra en modo de medio bloque, con dos
+    /// píxeles por carácter.
+    pub fn with_half_block(mut self, half_block: bool) -> Self {
+        self.half_block = half_block;
+        self
+    }
+
+    /// Devuelve la captura como texto con secuencias de escape ANSI.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells() {
+            let mut foreground = None;
+            let mut background = None;
+            for cell in row {
+                if foreground != Some(cell.foreground) {
+                    let Rgb { r, g, b } = cell.foreground;
+                    let _ = write!(out, "\x1b[38;2;{};{};{}m", r, g, b);
+                    foreground = Some(cell.foreground);
+                }
+                if background != Some(cell.background) {
+                    match cell.background {
+                        Some(Rgb { r, g, b }) => {
+                            let _ = write!(out, "\x1b[48;2;{};{};{}m", r, g, b);
+                        }
+                        None => out.push_str("\x1b[49m"),
+                    }
+                    background = Some(cell.background);
+                }
+                out.push(cell.symbol);
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+
+    /// Devuelve la captura como una página HTML independiente.
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Captura</title>\n</head>\n\
+             <body style=\"margin: 0; background: #000;\">\n\
+             <pre style=\"margin: 0; font-family: monospace; line-height: 1;\">",
+        );
+        for row in self.cells() {
+            // Los caracteres seguidos con los mismos colores comparten un `span`.
+            let mut run: Option<(Rgb, Option<Rgb>)> = None;
+            for cell in row {
+                let colors = (cell.foreground, cell.background);
+                if run != Some(colors) {
+                    if run.is_some() {
+                        out.push_str("</span>");
+                    }
+                    let _ = write!(out, "<span style=\"color: {}", hex(cell.foreground));
+                    if let Some(background) = cell.background {
+                        let _ = write!(out, "; background: {}", hex(background));
+                    }
+                    out.push_str("\">");
+                    run = Some(colors);
+                }
+                push_escaped(&mut out, cell.symbol);
+            }
+            if run.is_some() {
+                out.push_str("</span>");
+            }
+            out.push('\n');
+        }
+        out.push_str("</pre>\n</body>\n</html>\n");
+        out
+    }
+
+    /// Devuelve la captura como una imagen SVG independiente. En modo de medio
+    /// bloque cada píxel se dibuja como un rectángulo en lugar de un carácter.
+    pub fn to_svg(&self) -> String {
+        let cells = self.cells();
+        let cols = cells.first().map_or(0, Vec::len);
+        let width = cols * SVG_CELL_WIDTH;
+        let height = cells.len() * SVG_CELL_HEIGHT;
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = width,
+            h = height
+        );
+        let _ = writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"#000\"/>");
+        let _ = writeln!(
+            out,
+            "<g font-family=\"monospace\" font-size=\"{}\" xml:space=\"preserve\">",
+            SVG_CELL_HEIGHT - 2
+        );
+
+        for (y, row) in cells.iter().enumerate() {
+            let top = y * SVG_CELL_HEIGHT;
+            for (x, cell) in row.iter().enumerate() {
+                let left = x * SVG_CELL_WIDTH;
+                if self.half_block {
+                    let half = SVG_CELL_HEIGHT / 2;
+                    let bottom = cell.background.unwrap_or_default();
+                    let _ = writeln!(
+                        out,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                        left, top, SVG_CELL_WIDTH, half, hex(cell.foreground)
+                    );
+                    let _ = writeln!(
+                        out,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                        left, top + half, SVG_CELL_WIDTH, half, hex(bottom)
+                    );
+                } else if cell.symbol != ' ' {
+                    let _ = write!(
+                        out,
+                        "<text x=\"{}\" y=\"{}\" fill=\"{}\">",
+                        left,
+                        top + SVG_CELL_HEIGHT - 4,
+                        hex(cell.foreground)
+                    );
+                    push_escaped(&mut out, cell.symbol);
+                    out.push_str("</text>\n");
+                }
+            }
+        }
+
+        out.push_str("</g>\n</svg>\n");
+        out
+    }
+
+    /// Devuelve la captura como una imagen PPM binaria en la que cada píxel
+    /// del framebuffer es un bloque de color. Fuera del modo de medio bloque
+    /// los bloques son el doble de altos que de anchos, como los caracteres.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let block_width = PPM_BLOCK_SIZE;
+        let block_height = if self.half_block { PPM_BLOCK_SIZE } else { PPM_BLOCK_SIZE * 2 };
+        let width = self.framebuffer.width * block_width;
+        let height = self.framebuffer.height * block_height;
+
+        let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        out.reserve(width * height * 3);
+        for row in &self.framebuffer.pixels {
+            let colors: Vec<Rgb> = row.iter().map(|pixel| Rgb::from_color(pixel.fill_color())).collect();
+            for _ in 0..block_height {
+                for color in &colors {
+                    for _ in 0..block_width {
+                        out.extend_from_slice(&[color.r, color.g, color.b]);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Guarda la captura en un archivo con el formato indicado.
+    pub fn save(&self, path: impl AsRef<Path>, format: ScreenshotFormat) -> Result<(), ScreenshotError> {
+        let path = path.as_ref();
+        let contents = match format {
+            ScreenshotFormat::Ansi => self.to_ansi().into_bytes(),
+            ScreenshotFormat::Html => self.to_html().into_bytes(),
+            ScreenshotFormat::Svg => self.to_svg().into_bytes(),
+            ScreenshotFormat::Ppm => self.to_ppm(),
+        };
+        fs::write(path, contents).map_err(|err| ScreenshotError::Io(path.to_path_buf(), err))
+    }
+
+    /// Guarda la captura en un archivo, eligiendo el formato según su extensión.
+    pub fn save_as(&self, path: impl AsRef<Path>) -> Result<(), ScreenshotError> {
+        let path = path.as_ref();
+        let format =
+            ScreenshotFormat::from_path(path).ok_or_else(|| ScreenshotError::UnknownFormat(path.to_path_buf()))?;
+        self.save(path, format)
+    }
+
+    /// Compone las celdas de la captura igual que se muestran en la terminal.
+    fn cells(&self) -> Vec<Vec<ShotCell>> {
+        let pixels_per_row = if self.half_block { 2 } else { 1 };
+        self.framebuffer
+            .pixels
+            .chunks(pixels_per_row)
+            .map(|rows| {
+                rows[0]
+                    .iter()
+                    .enumerate()
+                    .map(|(x, top)| {
+                        if self.half_block {
+                            let bottom = rows.get(1).map_or(Color::Black, |row| row[x].fill_color());
+                            ShotCell {
+                                symbol: '▀',
+                                foreground: Rgb::from_color(top.fill_color()),
+                                background: Some(Rgb::from_color(bottom)),
+                            }
+                        } else {
+                            ShotCell {
+                                symbol: top.symbol,
+                                foreground: Rgb::from_color(top.color),
+                                background: None,
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Devuelve un color en notación hexadecimal de CSS.
+fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// Añade un carácter a un documento HTML o SVG, escapando los reservados.
+fn push_escaped(out: &mut String, symbol: char) {
+    match symbol {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(symbol),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Pixel;
+
+    /// Un framebuffer de 3 × 2 con un `#` rojo arriba a la izquierda y un `<`
+    /// blanco abajo a la derecha.
+    fn framebuffer() -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(3, 2);
+        framebuffer.set_pixel(0, 0, Pixel::rgb(Rgb::new(255, 0, 0), '#'));
+        framebuffer.set_pixel(2, 1, Pixel::rgb(Rgb::new(255, 255, 255), '<'));
+        framebuffer
+    }
+
+    /// Separa una imagen PPM en su cabecera y sus datos.
+    fn split_ppm(ppm: &[u8]) -> (String, &[u8]) {
+        let header_len = ppm
+            .iter()
+            .enumerate()
+            .filter(|&(_, &byte)| byte == b'\n')
+            .nth(2)
+            .map(|(i, _)| i + 1)
+            .unwrap();
+        (String::from_utf8(ppm[..header_len].to_vec()).unwrap(), &ppm[header_len..])
+    }
+
+    #[test]
+    fn ansi_has_one_line_per_row_in_true_color() {
+        let fb = framebuffer();
+        let ansi = Screenshot::new(&fb).to_ansi();
+        let lines: Vec<_> = ansi.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("\x1b[38;2;255;0;0m\x1b[49m#"));
+        assert!(lines.iter().all(|line| line.ends_with("\x1b[0m")));
+
+        let half = Screenshot::new(&fb).with_half_block(true).to_ansi();
+        assert_eq!(half.lines().count(), 1);
+        assert_eq!(half.matches('▀').count(), 3);
+    }
+
+    #[test]
+    fn html_is_a_standalone_escaped_page() {
+        let fb = framebuffer();
+        let html = Screenshot::new(&fb).to_html();
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.ends_with("</html>\n"));
+        assert!(html.contains("<span style=\"color: #ff0000\">#</span>"));
+        assert!(html.contains("\">&lt;</span>"));
+    }
+
+    #[test]
+    fn svg_is_sized_in_character_cells() {
+        let fb = framebuffer();
+        let svg = Screenshot::new(&fb).to_svg();
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" height=\"32\" viewBox=\"0 0 24 32\">\n"
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<text ").count(), 2);
+        assert!(svg.contains(">&lt;</text>"));
+
+        let half = Screenshot::new(&fb).with_half_block(true).to_svg();
+        assert!(half.contains("width=\"24\" height=\"16\""));
+        // El fondo y dos rectángulos por carácter.
+        assert_eq!(half.matches("<rect ").count(), 1 + 3 * 2);
+    }
+
+    #[test]
+    fn ppm_has_a_block_of_color_per_pixel() {
+        let fb = framebuffer();
+        let ppm = Screenshot::new(&fb).to_ppm();
+        let (header, data) = split_ppm(&ppm);
+        assert_eq!(header, "P6\n12 16\n255\n");
+        assert_eq!(data.len(), 12 * 16 * 3);
+        assert_eq!(&data[..3], &[255, 0, 0]);
+        assert_eq!(&data[data.len() - 3..], &[255, 255, 255]);
+
+        let half = Screenshot::new(&fb).with_half_block(true).to_ppm();
+        let (header, data) = split_ppm(&half);
+        assert_eq!(header, "P6\n12 8\n255\n");
+        assert_eq!(data.len(), 12 * 8 * 3);
+    }
+
+    #[test]
+    fn save_as_picks_the_format_from_the_extension() {
+        let dir = std::env::temp_dir().join(format!("raytracer-maze-screenshot-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fb = framebuffer();
+        let shot = Screenshot::new(&fb);
+
+        for format in ScreenshotFormat::ALL {
+            let path = dir.join(format!("captura.{}", format.extension()));
+            assert_eq!(ScreenshotFormat::from_path(&path), Some(format));
+            shot.save_as(&path).unwrap();
+        }
+        assert!(fs::read(dir.join("captura.ppm")).unwrap().starts_with(b"P6\n"));
+        assert!(fs::read_to_string(dir.join("captura.svg")).unwrap().starts_with("<svg "));
+
+        let unknown = dir.join("captura.png");
+        assert!(matches!(shot.save_as(&unknown), Err(ScreenshotError::UnknownFormat(_))));
+        assert!(!unknown.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use crate::game::{Cell, Entity, EntityType};
+use crate::graphics::Pixel;
+use crate::math::Vec2;
+
+/// Un objeto que se dibuja en la vista en primera persona como una imagen plana
+/// que siempre mira hacia la cámara (billboard).
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    /// La posición del sprite en el mapa.
+    pub position: Vec2,
+    /// El píxel con el que se rellena el sprite.
+    pub pixel: Pixel,
+    /// La altura del sprite relativa a la de una pared a la misma distancia.
+    pub scale: f32,
+}
+
+impl Sprite {
+    /// Crea un nuevo sprite.
+    pub fn new(position: Vec2, pixel: Pixel, scale: f32) -> Self {
+        Self {
+            position,
+            pixel,
+            scale,
+        }
+    }
+
+    /// Crea el sprite de una celda del mapa, centrado en ella, si el tipo de celda
+    /// se dibuja como sprite.
+    pub fn from_cell(x: usize, y: usize, cell: Cell) -> Option<Self> {
+        cell.sprite_scale().map(|scale| {
+            Self::new(Vec2::new(x as f32 + 0.5, y as f32 + 0.5), cell.pixel(), scale)
+        })
+    }
+
+    /// Crea el sprite de una entidad. El jugador no se dibuja en su propia vista.
+    pub fn from_entity(entity: &Entity) -> Option<Self> {
+        let scale = match entity.entity_type {
+            EntityType::Player => return None,
+            EntityType::Enemy => 0.7,
+            EntityType::Item => 0.35,
+            EntityType::Decoration => 0.6,
+        };
+        Some(Self::new(entity.position, entity.pixel, scale))
+    }
+}
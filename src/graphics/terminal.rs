@@ -0,0 +1,91 @@
+//! Preparación y restauración de la terminal.
+//!
+//! Mientras se juega, la terminal está en modo raw, en la pantalla alternativa
+//! y con el cursor oculto. Si la terminal lo admite, además informa de cuándo
+//! se suelta cada tecla. `TerminalSession` activa todo eso al crearse y lo
+//! deshace al destruirse, incluso si el programa entra en pánico, para que la
+//! terminal del usuario quede siempre utilizable.
+
+use std::io::{self, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    execute,
+    style::ResetColor,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+/// Garantiza que el gancho de pánico se instale una sola vez.
+static PANIC_HOOK: Once = Once::new();
+/// Indica si se activaron las mejoras del teclado y hay que desactivarlas al salir.
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+/// Una sesión de juego en la terminal. La terminal se restaura al destruirla.
+pub struct TerminalSession {
+    key_release_events: bool,
+}
+
+impl TerminalSession {
+    /// Entra en la pantalla alternativa, activa el modo raw, oculta el cursor y,
+    /// si la terminal lo admite, pide los eventos de tecla soltada. También
+    /// instala un gancho de pánico que restaura la terminal antes de mostrar el
+    /// mensaje de error.
+    pub fn enter() -> io::Result<Self> {
+        PANIC_HOOK.call_once(|| {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                let _ = restore_terminal();
+                previous(info);
+            }));
+        });
+
+        // Si algo falla a medio camino, la sesión ya creada deshace lo activado.
+        let mut session = Self { key_release_events: false };
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), Hide)?;
+
+        // En Windows la consola siempre informa de las teclas soltadas; en el
+        // resto, solo las terminales con el protocolo de teclado de kitty.
+        if cfg!(windows) {
+            session.key_release_events = true;
+        } else if terminal::supports_keyboard_enhancement().unwrap_or(false) {
+            let flags =
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES | KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+            execute!(io::stdout(), PushKeyboardEnhancementFlags(flags))?;
+            KEYBOARD_ENHANCED.store(true, Ordering::SeqCst);
+            session.key_release_events = true;
+        }
+        Ok(session)
+    }
+
+    /// Indica si la terminal informa de cuándo se suelta cada tecla. Si no, una
+    /// tecla mantenida solo llega como pulsaciones repetidas por el sistema.
+    pub fn key_release_events(&self) -> bool {
+        self.key_release_events
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+/// Deja la terminal como estaba antes de jugar: desactiva las mejoras del
+/// teclado y el modo raw, muestra el cursor y sale de la pantalla alternativa.
+/// Se puede llamar aunque la terminal ya esté restaurada.
+pub fn restore_terminal() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    if KEYBOARD_ENHANCED.swap(false, Ordering::SeqCst) {
+        let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+    }
+    let raw_mode = terminal::disable_raw_mode();
+    execute!(stdout, ResetColor, Show, LeaveAlternateScreen)?;
+    stdout.flush()?;
+    raw_mode
+}
@@ -0,0 +1,311 @@
+//! Texturas de pared para la vista en primera persona.
+//!
+//! Las texturas se definen en un archivo de texto con un bloque por tipo de
+//! pared. Cada bloque indica el glifo de la celda a la que se aplica, una paleta
+//! de colores con nombre, los caracteres de la textura y, con la misma forma, la
+//! clave de color de cada carácter:
+//!
+//! ```text
+//! texture #
+//! color r dark_red
+//! color m grey
+//! glyphs
+//! ▓▓▓█▓▓▓█
+//! ████████
+//! colors
+//! rrrmrrrm
+//! mmmmmmmm
+//! end
+//! ```
+//!
+//! Los nombres de color son los de `crossterm` (`dark_red`, `grey`, ...). Las
+//! líneas vacías y las que empiezan con `;` se ignoran. Los espacios al final
+//! de una fila de `glyphs` son píxeles de la textura, no se recortan.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::style::Color;
+
+use crate::game::{data_paths, Cell};
+use crate::graphics::Pixel;
+
+/// Ruta, relativa a los directorios de datos, del archivo de texturas por defecto.
+pub const DEFAULT_TEXTURE_FILE: &str = "textures/walls.txt";
+
+/// Texturas incluidas en el ejecutable, usadas si no se encuentra el archivo.
+const BUILTIN_TEXTURES: &str = include_str!("../../textures/walls.txt");
+
+/// Una textura: una cuadrícula de píxeles que se estira sobre una cara de pared.
+#[derive(Clone, Debug)]
+pub struct Texture {
+    /// El ancho de la textura en píxeles.
+    pub width: usize,
+    /// La altura de la textura en píxeles.
+    pub height: usize,
+    texels: Vec<Pixel>,
+}
+
+impl Texture {
+    /// Devuelve el píxel en las coordenadas de textura `(u, v)`, ambas en `[0, 1)`.
+    pub fn sample(&self, u: f32, v: f32) -> Pixel {
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.texels[y * self.width + x]
+    }
+}
+
+/// Describe un error en una línea del archivo de texturas.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextureErrorKind {
+    /// Se esperaba `texture <glifo>` al inicio de un bloque.
+    ExpectedTexture,
+    /// El glifo no corresponde a ninguna celda sólida.
+    NotAWall(String),
+    /// La paleta usa un nombre de color desconocido.
+    UnknownColor(String),
+    /// Una línea `color` no tiene la forma `color <clave> <nombre>`.
+    MalformedColor,
+    /// La cuadrícula de colores usa una clave que no está en la paleta.
+    UnknownColorKey(char),
+    /// Las cuadrículas de glifos y colores no tienen la misma forma o están vacías.
+    SizeMismatch,
+    /// El archivo terminó antes de cerrar un bloque con `end`.
+    UnexpectedEnd,
+}
+
+/// Error producido al cargar un archivo de texturas.
+#[derive(Debug)]
+pub enum TextureError {
+    /// No se pudo leer el archivo.
+    Io(PathBuf, io::Error),
+    /// El archivo contiene un error en la línea indicada (base 1).
+    Parse { line: usize, kind: TextureErrorKind },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            TextureError::Parse { line, kind } => {
+                write!(f, "línea {}: ", line)?;
+                match kind {
+                    TextureErrorKind::ExpectedTexture => {
+                        write!(f, "se esperaba `texture <glifo de pared>`")
+                    }
+                    TextureErrorKind::NotAWall(glyph) => {
+                        write!(f, "`{}` no es el glifo de una pared", glyph)
+                    }
+                    TextureErrorKind::UnknownColor(name) => write!(f, "color desconocido `{}`", name),
+                    TextureErrorKind::MalformedColor => {
+                        write!(f, "se esperaba `color <clave> <nombre>`")
+                    }
+                    TextureErrorKind::UnknownColorKey(key) => {
+                        write!(f, "la clave de color {:?} no está en la paleta", key)
+                    }
+                    TextureErrorKind::SizeMismatch => write!(
+                        f,
+                        "las cuadrículas de glifos y colores deben tener la misma forma"
+                    ),
+                    TextureErrorKind::UnexpectedEnd => {
+                        write!(f, "falta `end` al final de la textura")
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// Las texturas de cada tipo de pared.
+#[derive(Clone, Debug, Default)]
+pub struct TextureSet {
+    textures: HashMap<Cell, Texture>,
+}
+
+impl TextureSet {
+    /// Devuelve la textura de un tipo de celda, si tiene una. Las puertas usan
+    /// la misma textura en cualquier punto de su animación.
+    pub fn get(&self, cell: Cell) -> Option<&Texture> {
+        self.textures.get(&cell.closed())
+    }
+
+    /// Devuelve las texturas incluidas en el ejecutable.
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_TEXTURES).expect("las texturas incluidas deben ser válidas")
+    }
+
+    /// Carga las texturas del archivo por defecto si existe o, si no, las incluidas
+    /// en el ejecutable.
+    pub fn discover() -> Result<Self, TextureError> {
+        match data_paths(DEFAULT_TEXTURE_FILE).iter().find(|path| path.is_file()) {
+            Some(path) => Self::load(path),
+            None => Ok(Self::builtin()),
+        }
+    }
+
+    /// Carga las texturas de un archivo.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TextureError> {
+        let path = path.as_ref();
+        let source =
+            fs::read_to_string(path).map_err(|err| TextureError::Io(path.to_path_buf(), err))?;
+        Self::parse(&source)
+    }
+
+    /// Interpreta un archivo de texturas.
+    pub fn parse(source: &str) -> Result<Self, TextureError> {
+        let error = |line: usize, kind: TextureErrorKind| TextureError::Parse { line, kind };
+        let mut lines = source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.strip_suffix('\r').unwrap_or(line)))
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with(';'));
+
+        let mut textures = HashMap::new();
+
+        while let Some((line_no, line)) = lines.next() {
+            let glyph = line
+                .strip_prefix("texture ")
+                .map(str::trim)
+                .ok_or_else(|| error(line_no, TextureErrorKind::ExpectedTexture))?;
+            let cell = glyph
+                .chars()
+                .next()
+                .filter(|_| glyph.chars().count() == 1)
+                .and_then(Cell::from_glyph)
+                .filter(|cell| cell.is_solid())
+                .ok_or_else(|| error(line_no, TextureErrorKind::NotAWall(glyph.to_string())))?;
+
+            let mut palette = HashMap::new();
+            let mut glyph_rows: Vec<Vec<char>> = Vec::new();
+            let mut color_rows: Vec<(usize, Vec<char>)> = Vec::new();
+            let mut section = None;
+            let mut last_line = line_no;
+
+            loop {
+                let (line_no, line) = lines
+                    .next()
+                    .ok_or_else(|| error(last_line, TextureErrorKind::UnexpectedEnd))?;
+                last_line = line_no;
+
+                match line.trim_end() {
+                    "end" => break,
+                    keyword @ ("glyphs" | "colors") => section = Some(keyword),
+                    _ if section.is_none() => {
+                        let mut parts = line.split_whitespace();
+                        let (Some("color"), Some(key), Some(name), None) =
+                            (parts.next(), parts.next(), parts.next(), parts.next())
+                        else {
+                            return Err(error(line_no, TextureErrorKind::MalformedColor));
+                        };
+                        let mut key_chars = key.chars();
+                        let (Some(key), None) = (key_chars.next(), key_chars.next()) else {
+                            return Err(error(line_no, TextureErrorKind::MalformedColor));
+                        };
+                        let color = Color::try_from(name)
+                            .map_err(|_| error(line_no, TextureErrorKind::UnknownColor(name.to_string())))?;
+                        palette.insert(key, color);
+                    }
+                    _ if section == Some("glyphs") => glyph_rows.push(line.chars().collect()),
+                    // Las claves de color nunca son espacios, así que estas filas sí se recortan.
+                    _ => color_rows.push((line_no, line.trim_end().chars().collect())),
+                }
+            }
+
+            let width = glyph_rows.first().map_or(0, Vec::len);
+            let same_shape = width > 0
+                && glyph_rows.len() == color_rows.len()
+                && glyph_rows.iter().all(|row| row.len() == width)
+                && color_rows.iter().all(|(_, row)| row.len() == width);
+            if !same_shape {
+                return Err(error(last_line, TextureErrorKind::SizeMismatch));
+            }
+
+            let mut texels = Vec::with_capacity(width * glyph_rows.len());
+            for (glyphs, (line_no, keys)) in glyph_rows.iter().zip(&color_rows) {
+                for (&symbol, &key) in glyphs.iter().zip(keys) {
+                    let color = *palette
+                        .get(&key)
+                        .ok_or_else(|| error(*line_no, TextureErrorKind::UnknownColorKey(key)))?;
+                    texels.push(Pixel::new(color, symbol));
+                }
+            }
+
+            textures.insert(
+                cell,
+                Texture {
+                    width,
+                    height: glyph_rows.len(),
+                    texels,
+                },
+            );
+        }
+
+        Ok(Self { textures })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(source: &str) -> (usize, TextureErrorKind) {
+        match TextureSet::parse(source) {
+            Err(TextureError::Parse { line, kind }) => (line, kind),
+            other => panic!("se esperaba un error de formato, se obtuvo {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn parses_a_texture_set() {
+        let set = TextureSet::parse(
+            "; comentario\ntexture #\ncolor r dark_red\ncolor m grey\nglyphs\n▓█\n██\n▒▒\ncolors\nrm\nmm\nrr\nend\n",
+        )
+        .unwrap();
+        let texture = set.get(Cell::Wall).unwrap();
+        assert_eq!((texture.width, texture.height), (2, 3));
+        assert_eq!(texture.sample(0.0, 0.0), Pixel::new(Color::DarkRed, '▓'));
+        assert_eq!(texture.sample(0.9, 0.0), Pixel::new(Color::Grey, '█'));
+        assert_eq!(texture.sample(0.5, 0.9), Pixel::new(Color::DarkRed, '▒'));
+        assert!(set.get(Cell::Stone).is_none());
+
+        assert!(TextureSet::builtin().get(Cell::Wall).is_some());
+    }
+
+    #[test]
+    fn keeps_a_trailing_blank_column() {
+        let set = TextureSet::parse("texture %\ncolor s grey\nglyphs\n█ \n▓ \ncolors\nss\nss \nend\n").unwrap();
+        let texture = set.get(Cell::Stone).unwrap();
+        assert_eq!((texture.width, texture.height), (2, 2));
+        assert_eq!(texture.sample(0.9, 0.9), Pixel::new(Color::Grey, ' '));
+    }
+
+    #[test]
+    fn reports_malformed_textures() {
+        assert_eq!(
+            parse_error("texture #\ncolor r red\nglyphs\n██\n█\ncolors\nrr\nrr\nend\n"),
+            (9, TextureErrorKind::SizeMismatch)
+        );
+        assert_eq!(
+            parse_error("texture .\nend\n"),
+            (1, TextureErrorKind::NotAWall(".".to_string()))
+        );
+        assert_eq!(
+            parse_error("texture X\nend\n"),
+            (1, TextureErrorKind::NotAWall("X".to_string()))
+        );
+        assert_eq!(
+            parse_error("texture #\ncolor r crimson\nend\n"),
+            (2, TextureErrorKind::UnknownColor("crimson".to_string()))
+        );
+        assert_eq!(
+            parse_error("texture #\ncolor r red\nglyphs\n█\ncolors\nx\nend\n"),
+            (6, TextureErrorKind::UnknownColorKey('x'))
+        );
+        assert_eq!(parse_error("texture #\nglyphs\n█\n"), (3, TextureErrorKind::UnexpectedEnd));
+    }
+}
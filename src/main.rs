@@ -1,316 +1,353 @@
-use crossterm::{
-    event::{Event, KeyCode, KeyEvent, poll, read},
-    terminal::size,
-    cursor::Hide,
-    ExecutableCommand,
-};
+use crossterm::event::{Event, KeyEvent, KeyEventKind, poll, read};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 use raytracer_maze::{
-    Camera, CameraMode, Framebuffer, GameRenderer, 
-    GameState, Player, Level,
+    ColorMode, Game, GameConfig, Keymap, Level, LevelPack, MazeAlgorithm, TerminalBackend,
+    TerminalSession, TextureSet, DEFAULT_MAZE_SIZE, DEFAULT_MOVE_SPEED, DEFAULT_PLAYER_RADIUS,
+    DEFAULT_TURN_SPEED, MAX_MAZE_SIZE, MAX_MOVE_SPEED,
 };
 
-struct FpsCounter {
-    frame_count: u32,
-    last_time: Instant,
-    fps: f32,
+/// Opciones de línea de comandos.
+struct Options {
+    /// Directorio del paquete de niveles indicado con `--pack`.
+    pack_dir: Option<PathBuf>,
+    /// Semilla de los laberintos aleatorios indicada con `--seed`.
+    seed: Option<u64>,
+    /// Tamaño en habitaciones de los laberintos aleatorios (`--size`).
+    maze_size: (usize, usize),
+    /// Algoritmo de generación indicado con `--algorithm`.
+    algorithm: Option<MazeAlgorithm>,
+    /// Colores de la terminal, detectados o indicados con `--color`.
+    color_mode: ColorMode,
+    /// Muestra dos píxeles por carácter (`--half-block`).
+    half_block: bool,
+    /// Dibuja la vista cenital con puntos Braille (`--braille`).
+    braille: bool,
+    /// Velocidad del jugador en celdas por segundo (`--speed`).
+    move_speed: f32,
+    /// Velocidad de giro en radianes por segundo (`--turn-speed`).
+    turn_speed: f32,
+    /// Radio del jugador en celdas (`--radius`).
+    player_radius: f32,
+    /// Archivo de teclas indicado con `--keys`.
+    keys_file: Option<PathBuf>,
+    /// Rutas a validar con `--validate`; `Some` aunque esté vacía si se pidió validar.
+    validate: Option<Vec<PathBuf>>,
 }
 
-impl FpsCounter {
-    fn new() -> Self {
-        Self {
-            frame_count: 0,
-            last_time: Instant::now(),
-            fps: 0.0,
-        }
-    }
+const USAGE: &str = "Uso: raytracer-maze [opciones]
+       raytracer-maze --validate [archivo o directorio...]
 
-    fn update(&mut self) {
-        self.frame_count += 1;
-        let elapsed = self.last_time.elapsed();
-        
-        if elapsed >= Duration::from_millis(500) {
-            self.fps = self.frame_count as f32 / elapsed.as_secs_f32();
-            self.frame_count = 0;
-            self.last_time = Instant::now();
-        }
-    }
+Opciones:
+  --pack <directorio>    Carga el paquete de niveles del directorio indicado
+  --seed <número>        Semilla de los laberintos aleatorios (modo infinito)
+  --size <ancho>x<alto>  Tamaño en habitaciones de los laberintos aleatorios,
+                         de 2x2 a 100x100
+  --algorithm <nombre>   Algoritmo de generación: backtracker, prim, kruskal,
+                         eller o braid (por defecto se elige según la semilla)
+  --color <modo>         Colores de la terminal: truecolor, 256 o 16 (por
+                         defecto se detecta con COLORTERM y TERM)
+  --half-block           Duplica la resolución vertical dibujando dos
+                         píxeles por carácter (se alterna con H al jugar)
+  --braille              Dibuja la vista cenital con puntos Braille, con
+                         2x4 puntos por carácter (se alterna con B al jugar)
+  --speed <celdas/s>     Velocidad del jugador, hasta 10 (por defecto 3)
+  --turn-speed <rad/s>   Velocidad de giro (por defecto 1.6)
+  --radius <celdas>      Radio del jugador al chocar con las paredes, menor
+                         que 0.5 (por defecto 0.2)
+  --keys <archivo>       Carga las teclas del archivo indicado y guarda en
+                         él las que se cambien en el juego (por defecto
+                         keys.txt)
+  --validate [rutas...]  Valida los niveles indicados (o el paquete por
+                         defecto) sin iniciar el juego
+  -h, --help             Muestra esta ayuda";
 
-    fn get_fps(&self) -> f32 {
-        self.fps
-    }
+fn parse_size(text: &str) -> Option<(usize, usize)> {
+    let (width, height) = match text.split_once('x') {
+        Some((width, height)) => (width.parse().ok()?, height.parse().ok()?),
+        None => {
+            let side = text.parse().ok()?;
+            (side, side)
+        }
+    };
+    let valid = |side: usize| (2..=MAX_MAZE_SIZE).contains(&side);
+    (valid(width) && valid(height)).then_some((width, height))
 }
 
-fn create_levels() -> Vec<Level> {
-    vec![
-        Level::new(
-            vec![
-                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-                vec![1, 2, 0, 0, 5, 1, 0, 0, 0, 1],
-                vec![1, 0, 1, 0, 0, 0, 0, 1, 0, 1],
-                vec![1, 0, 0, 5, 0, 1, 0, 0, 0, 1],
-                vec![1, 0, 1, 0, 0, 0, 5, 0, 0, 1],
-                vec![1, 0, 0, 0, 1, 0, 0, 1, 3, 1],
-                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-            ],
-            3,
-            "Nivel 1: El Comienzo"
-        ),
-        Level::new(
-            vec![
-                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-                vec![1, 2, 0, 0, 5, 1, 0, 5, 0, 0, 0, 1],
-                vec![1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 0, 1],
-                vec![1, 5, 1, 0, 0, 0, 0, 0, 1, 0, 1, 1],
-                vec![1, 0, 0, 0, 1, 1, 1, 0, 0, 5, 0, 1],
-                vec![1, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 1],
-                vec![1, 0, 0, 5, 0, 0, 1, 0, 0, 0, 0, 1],
-                vec![1, 0, 1, 1, 0, 0, 1, 0, 1, 0, 3, 1],
-                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-            ],
-            5,
-            "Nivel 2: El Laberinto"
-        ),
-        Level::new(
-            vec![
-                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-                vec![1, 2, 0, 0, 5, 0, 0, 1, 0, 5, 0, 0, 0, 1],
-                vec![1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 1, 1, 0, 1],
-                vec![1, 5, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1],
-                vec![1, 0, 1, 0, 0, 0, 0, 1, 1, 1, 0, 1, 5, 1],
-                vec![1, 0, 1, 0, 1, 1, 0, 0, 0, 1, 0, 1, 0, 1],
-                vec![1, 5, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 5, 1],
-                vec![1, 1, 1, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1],
-                vec![1, 0, 0, 5, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1],
-                vec![1, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 0, 3, 1],
-                vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
-            ],
-            8,
-            "Nivel 3: El Desafío Final"
-        ),
-    ]
+fn parse_positive(text: &str) -> Option<f32> {
+    text.parse().ok().filter(|speed: &f32| speed.is_finite() && *speed > 0.0)
 }
 
-fn main() {
-    let mut levels = create_levels();
-    let mut current_level = 0;
-    let mut collected_items = 0;
-    let mut selected_level = 0;
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        pack_dir: None,
+        seed: None,
+        maze_size: DEFAULT_MAZE_SIZE,
+        algorithm: None,
+        color_mode: ColorMode::detect(),
+        half_block: false,
+        braille: false,
+        move_speed: DEFAULT_MOVE_SPEED,
+        turn_speed: DEFAULT_TURN_SPEED,
+        player_radius: DEFAULT_PLAYER_RADIUS,
+        keys_file: None,
+        validate: None,
+    };
+    let mut args = std::env::args().skip(1);
 
-    let (cols, rows) = size().unwrap();
-    let cols = cols as usize;
-    let rows = rows as usize;
-
-    let mut player = Player::from_map(&levels[current_level].map);
-    let mut camera = Camera::new();
-    let mut game_state = GameState::Menu;
-    let mut fps_counter = FpsCounter::new();
+    while let Some(arg) = args.next() {
+        if let Some(paths) = options.validate.as_mut() {
+            if !arg.starts_with('-') {
+                paths.push(PathBuf::from(arg));
+                continue;
+            }
+        }
 
-    crossterm::terminal::enable_raw_mode().unwrap();
-    let mut stdout = std::io::stdout();
-    stdout.execute(Hide).unwrap();
+        match arg.as_str() {
+            "--pack" => {
+                let dir = args.next().ok_or("--pack requiere un directorio")?;
+                options.pack_dir = Some(PathBuf::from(dir));
+            }
+            "--seed" => {
+                let seed = args.next().ok_or("--seed requiere un número")?;
+                options.seed = Some(seed.parse().map_err(|_| format!("semilla inválida `{}`", seed))?);
+            }
+            "--size" => {
+                let size = args.next().ok_or("--size requiere un tamaño")?;
+                options.maze_size = parse_size(&size).ok_or_else(|| {
+                    format!(
+                        "tamaño inválido `{}` (usa por ejemplo 12x8, entre 2x2 y {}x{})",
+                        size, MAX_MAZE_SIZE, MAX_MAZE_SIZE
+                    )
+                })?;
+            }
+            "--algorithm" => {
+                let name = args.next().ok_or("--algorithm requiere un nombre")?;
+                options.algorithm = Some(
+                    MazeAlgorithm::from_name(&name)
+                        .ok_or_else(|| format!("algoritmo desconocido `{}`", name))?,
+                );
+            }
+            "--color" => {
+                let name = args.next().ok_or("--color requiere un modo")?;
+                options.color_mode = ColorMode::from_name(&name)
+                    .ok_or_else(|| format!("modo de color desconocido `{}` (usa truecolor, 256 o 16)", name))?;
+            }
+            "--half-block" => options.half_block = true,
+            "--braille" => options.braille = true,
+            "--speed" => {
+                let speed = args.next().ok_or("--speed requiere una velocidad")?;
+                options.move_speed = parse_positive(&speed)
+                    .filter(|&speed| speed <= MAX_MOVE_SPEED)
+                    .ok_or_else(|| {
+                        format!("velocidad inválida `{}` (debe ser mayor que 0 y como mucho {})", speed, MAX_MOVE_SPEED)
+                    })?;
+            }
+            "--turn-speed" => {
+                let speed = args.next().ok_or("--turn-speed requiere una velocidad")?;
+                options.turn_speed = parse_positive(&speed).ok_or_else(|| format!("velocidad inválida `{}`", speed))?;
+            }
+            "--radius" => {
+                let radius = args.next().ok_or("--radius requiere un radio")?;
+                options.player_radius = parse_positive(&radius)
+                    .filter(|&radius| radius < 0.5)
+                    .ok_or_else(|| format!("radio inválido `{}` (debe ser mayor que 0 y menor que 0.5)", radius))?;
+            }
+            "--keys" => {
+                let file = args.next().ok_or("--keys requiere un archivo")?;
+                options.keys_file = Some(PathBuf::from(file));
+            }
+            "--validate" => options.validate = Some(Vec::new()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            other => return Err(format!("argumento desconocido `{}`", other)),
+        }
+    }
 
-    let target_frame_time = Duration::from_millis(16);
-    let mut running = true;
-    let mut first_render = true;
+    Ok(options)
+}
 
-    let mut renderer = GameRenderer::new(
-        cols,
-        rows,
-        levels[current_level].get_width(),
-        levels[current_level].get_height(),
-    );
-    let mut fb_topdown = Framebuffer::new(
-        levels[current_level].get_width() * renderer.get_cell_width(),
-        levels[current_level].get_height() * renderer.get_cell_height(),
-    );
-    let mut fb_firstperson = Framebuffer::new(cols, rows.saturating_sub(4));
+/// Valida un archivo de nivel y muestra sus errores y advertencias.
+/// Devuelve `true` si el nivel no tiene errores.
+fn validate_level_file(path: &Path) -> bool {
+    let level = match fs::read_to_string(path) {
+        Ok(source) => match Level::parse(&source) {
+            Ok(level) => level,
+            Err(err) => {
+                println!("✗ {}: {}", path.display(), err);
+                return false;
+            }
+        },
+        Err(err) => {
+            println!("✗ {}: no se pudo leer el archivo: {}", path.display(), err);
+            return false;
+        }
+    };
 
-    while running {
-        let frame_start = Instant::now();
+    let report = level.validate();
+    let mark = if report.is_valid() { '✓' } else { '✗' };
+    println!("{} {} ({})", mark, path.display(), level.name);
+    // Cada problema ocupa una línea, con su etiqueta de error o advertencia.
+    for line in report.to_string().lines() {
+        println!("    {}", line);
+    }
+    report.is_valid()
+}
 
-        match game_state {
-            GameState::Menu => {
-                renderer.show_menu();
+/// Valida los niveles de las rutas indicadas, que pueden ser archivos de nivel o
+/// directorios de paquete. Sin rutas, valida el paquete que usaría el juego.
+/// Devuelve `true` si ningún nivel tiene errores.
+fn run_validation(options: &Options, paths: &[PathBuf]) -> bool {
+    let packs = if paths.is_empty() {
+        vec![LevelPack::discover(options.pack_dir.as_deref())]
+    } else {
+        paths
+            .iter()
+            .filter(|path| path.is_dir())
+            .map(LevelPack::load)
+            .collect()
+    };
 
-                if poll(Duration::from_millis(16)).unwrap() {
-                    if let Ok(Event::Key(KeyEvent { code, .. })) = read() {
-                        match code {
-                            KeyCode::Enter => {
-                                game_state = GameState::LevelSelect;
-                            },
-                            KeyCode::Char('x') | KeyCode::Esc => running = false,
-                            _ => {}
-                        }
-                    }
+    let mut valid = true;
+    for path in paths.iter().filter(|path| !path.is_dir()) {
+        valid &= validate_level_file(path);
+    }
+    for pack in packs {
+        match pack {
+            Ok(pack) => {
+                println!("Paquete «{}» ({})", pack.name, pack.dir.display());
+                for entry in &pack.entries {
+                    valid &= validate_level_file(&entry.file);
                 }
             }
-
-            GameState::LevelSelect => {
-                renderer.show_level_select(selected_level, &levels);
-
-                if poll(Duration::from_millis(16)).unwrap() {
-                    if let Ok(Event::Key(KeyEvent { code, .. })) = read() {
-                        match code {
-                            KeyCode::Up | KeyCode::Char('w') => {
-                                if selected_level > 0 {
-                                    selected_level -= 1;
-                                }
-                            },
-                            KeyCode::Down | KeyCode::Char('s') => {
-                                if selected_level < levels.len() - 1 {
-                                    selected_level += 1;
-                                }
-                            },
-                            KeyCode::Char('1') => selected_level = 0,
-                            KeyCode::Char('2') => selected_level = 1.min(levels.len() - 1),
-                            KeyCode::Char('3') => selected_level = 2.min(levels.len() - 1),
-                            KeyCode::Enter => {
-                                // Reiniciar el nivel seleccionado
-                                levels = create_levels(); // Recrear todos los niveles
-                                current_level = selected_level;
-                                collected_items = 0;
-                                player = Player::from_map(&levels[current_level].map);
-                                
-                                renderer = GameRenderer::new(
-                                    cols,
-                                    rows,
-                                    levels[current_level].get_width(),
-                                    levels[current_level].get_height(),
-                                );
-                                fb_topdown = Framebuffer::new(
-                                    levels[current_level].get_width() * renderer.get_cell_width(),
-                                    levels[current_level].get_height() * renderer.get_cell_height(),
-                                );
-                                
-                                game_state = GameState::Playing;
-                                first_render = true;
-                            },
-                            KeyCode::Esc => game_state = GameState::Menu,
-                            _ => {}
-                        }
-                    }
-                }
+            Err(err) => {
+                println!("✗ {}", err);
+                valid = false;
             }
+        }
+    }
+    valid
+}
 
-            GameState::Playing => {
-                let level = &mut levels[current_level];
-                
-                // Verificar si el jugador está en un item y recogerlo
-                let (px, py) = player.get_grid_position();
-                if level.collect_item(px, py) {
-                    collected_items += 1;
-                    player.has_moved = true; // Forzar re-render
-                }
+/// Duración de cada cuadro (unos 60 FPS).
+const FRAME_TIME: Duration = Duration::from_millis(16);
 
-                // Verificar victoria del nivel
-                if player.is_at_goal(&level.map) && collected_items >= level.required_items {
-                    if current_level < levels.len() - 1 {
-                        // Siguiente nivel
-                        current_level += 1;
-                        collected_items = 0;
-                        player = Player::from_map(&levels[current_level].map);
-                        
-                        renderer = GameRenderer::new(
-                            cols,
-                            rows,
-                            levels[current_level].get_width(),
-                            levels[current_level].get_height(),
-                        );
-                        fb_topdown = Framebuffer::new(
-                            levels[current_level].get_width() * renderer.get_cell_width(),
-                            levels[current_level].get_height() * renderer.get_cell_height(),
-                        );
-                        
-                        first_render = true;
-                    } else {
-                        // Juego completado
-                        game_state = GameState::Victory;
-                        continue;
-                    }
-                }
+/// Ejecuta el juego en la terminal hasta que el jugador sale.
+fn run(game: &mut Game) -> io::Result<()> {
+    let mut backend = TerminalBackend::new();
+    let mut last_frame = Instant::now();
 
-                if player.has_moved || first_render {
-                    let level_ref = &levels[current_level];
-                    match camera.mode {
-                        CameraMode::TopDown => {
-                            renderer.render_top_down(
-                                &mut fb_topdown,
-                                &level_ref.map,
-                                &player,
-                                &[],
-                            );
-                            renderer.display_framebuffer(&fb_topdown);
-                        }
-                        CameraMode::FirstPerson => {
-                            renderer.render_first_person(
-                                &mut fb_firstperson,
-                                &player,
-                                &level_ref.map,
-                            );
-                            renderer.display_framebuffer(&fb_firstperson);
-                        }
-                    }
-
-                    renderer.display_ui(
-                        fps_counter.get_fps(),
-                        player.position.x,
-                        player.position.y,
-                        collected_items,
-                        level_ref.required_items,
-                        current_level + 1,
-                        levels.len(),
-                        &level_ref.name,
-                    );
+    while game.is_running() {
+        let frame_start = Instant::now();
+        game.update(frame_start.duration_since(last_frame).as_secs_f32());
+        last_frame = frame_start;
+        game.render(&mut backend)?;
 
-                    player.has_moved = false;
-                    first_render = false;
-                }
+        // Atiende todas las teclas que lleguen durante lo que queda del cuadro,
+        // para poder, por ejemplo, avanzar y girar a la vez.
+        let frame_end = frame_start + FRAME_TIME;
+        while game.is_running() && poll(frame_end.saturating_duration_since(Instant::now()))? {
+            match read()? {
+                Event::Key(KeyEvent { code, kind, .. }) => match kind {
+                    KeyEventKind::Press | KeyEventKind::Repeat => game.handle_key(code),
+                    KeyEventKind::Release => game.handle_key_release(code),
+                },
+                // Se adapta la disposición en cuanto cambia el tamaño, sin
+                // esperar a que lo detecte el siguiente `render`.
+                Event::Resize(cols, rows) => game.resize(cols, rows),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
 
-                fps_counter.update();
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("Error: {}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
 
-                if poll(Duration::from_millis(1)).unwrap() {
-                    if let Ok(Event::Key(KeyEvent { code, .. })) = read() {
-                        match code {
-                            KeyCode::Char('w') | KeyCode::Up => 
-                                player.move_forward(0.15, &levels[current_level].map),
-                            KeyCode::Char('s') | KeyCode::Down => 
-                                player.move_backward(0.15, &levels[current_level].map),
-                            KeyCode::Char('a') | KeyCode::Left => 
-                                player.strafe(-0.15, &levels[current_level].map),
-                            KeyCode::Char('d') | KeyCode::Right => 
-                                player.strafe(0.15, &levels[current_level].map),
-                            KeyCode::Char('q') => player.rotate(-0.08),
-                            KeyCode::Char('e') => player.rotate(0.08),
-                            KeyCode::Char('c') => {
-                                camera.toggle_mode();
-                                player.has_moved = true;
-                            }
-                            KeyCode::Char('x') | KeyCode::Esc => running = false,
-                            _ => {}
-                        }
-                    }
-                }
+    if let Some(paths) = &options.validate {
+        let valid = run_validation(&options, paths);
+        std::process::exit(if valid { 0 } else { 1 });
+    }
 
-                let frame_time = frame_start.elapsed();
-                if frame_time < target_frame_time {
-                    std::thread::sleep(target_frame_time - frame_time);
-                }
+    let pack = match LevelPack::discover(options.pack_dir.as_deref()) {
+        Ok(pack) => pack,
+        Err(err) => {
+            eprintln!("Error al buscar el paquete de niveles: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let textures = match TextureSet::discover() {
+        Ok(textures) => textures,
+        Err(err) => {
+            eprintln!("Error al cargar las texturas: {}", err);
+            std::process::exit(1);
+        }
+    };
+    // Sin archivo de teclas se usan las teclas por defecto; los cambios hechos en
+    // el juego se guardan en esa misma ruta.
+    let keymap_path = options.keys_file.clone().unwrap_or_else(Keymap::default_path);
+    let keymap = if keymap_path.is_file() {
+        match Keymap::load(&keymap_path) {
+            Ok(keymap) => keymap,
+            Err(err) => {
+                eprintln!("Error al cargar las teclas: {}", err);
+                std::process::exit(1);
             }
+        }
+    } else {
+        Keymap::builtin()
+    };
+    let config = GameConfig {
+        seed: options.seed,
+        maze_size: options.maze_size,
+        algorithm: options.algorithm,
+        color_mode: options.color_mode,
+        half_block: options.half_block,
+        braille: options.braille,
+        move_speed: options.move_speed,
+        turn_speed: options.turn_speed,
+        player_radius: options.player_radius,
+        keymap,
+        keymap_path,
+        ..GameConfig::default()
+    };
+    let mut game = match Game::new(pack, textures, config) {
+        Ok(game) => game,
+        Err(err) => {
+            eprintln!("Error al cargar los niveles: {}", err);
+            std::process::exit(1);
+        }
+    };
 
-            GameState::Victory => {
-                renderer.show_victory();
+    let session = match TerminalSession::enter() {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("Error al preparar la terminal: {}", err);
+            std::process::exit(1);
+        }
+    };
+    game.set_key_release_events(session.key_release_events());
+    let result = run(&mut game);
+    // Restaura la terminal antes de escribir el resultado.
+    drop(session);
 
-                if poll(Duration::from_millis(16)).unwrap() {
-                    if let Ok(Event::Key(KeyEvent { .. })) = read() {
-                        running = false;
-                    }
-                }
-            }
+    match result {
+        Ok(()) => println!("¡Gracias por jugar! FPS promedio: {:.1}", game.fps()),
+        Err(err) => {
+            eprintln!("Error de la terminal: {}", err);
+            std::process::exit(1);
         }
     }
-
-    crossterm::terminal::disable_raw_mode().unwrap();
-    println!("\n¡Gracias por jugar! FPS promedio: {:.1}", fps_counter.get_fps());
-}
\ No newline at end of file
+}
@@ -1,7 +1,11 @@
 //! El módulo `math` provee estructuras y funciones para operaciones matemáticas básicas en 2D.
 
+pub mod rng;
+
+pub use rng::*;
+
 /// Representa un vector de 2 dimensiones con componentes de punto flotante.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
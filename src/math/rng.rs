@@ -0,0 +1,57 @@
+/// Generador de números pseudoaleatorios determinista (xorshift64*).
+///
+/// La misma semilla produce siempre la misma secuencia, lo que permite
+/// reproducir exactamente un laberinto generado.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Crea un generador a partir de una semilla.
+    pub fn new(seed: u64) -> Self {
+        // Mezcla la semilla con SplitMix64 para que semillas cercanas den
+        // secuencias distintas y el estado nunca sea cero.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self { state: z.max(1) }
+    }
+
+    /// Devuelve el siguiente número de 64 bits de la secuencia.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Devuelve un número en el rango `0..n`. `n` debe ser mayor que cero.
+    pub fn range(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Devuelve un número de punto flotante en el rango `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Devuelve `true` con la probabilidad indicada.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Elige un elemento al azar de un slice no vacío.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(items.len())]
+    }
+
+    /// Mezcla los elementos de un slice (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(i + 1);
+            items.swap(i, j);
+        }
+    }
+}